@@ -0,0 +1,19 @@
+//! Primitives for filesystem operations.
+
+use std::{
+    collections::hash_map::RandomState,
+    ffi::{OsStr, OsString},
+    hash::{BuildHasher, Hasher},
+};
+
+/// Returns a random name for a temporary file or directory, made of `prefix`,
+/// 16 random hex digits and `suffix`.
+///
+/// The name is hard to guess, so other users can't create it first.
+pub fn temp_name<S: AsRef<OsStr>>(prefix: S, suffix: &str) -> OsString {
+    // `RandomState` is seeded by the OS, and has new keys on each call.
+    let bits = RandomState::new().build_hasher().finish();
+    let mut name = prefix.as_ref().to_owned();
+    name.push(format!("{:016x}{}", bits, suffix));
+    name
+}
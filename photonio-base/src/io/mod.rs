@@ -2,6 +2,9 @@
 
 pub use std::io::{Error, Result, SeekFrom};
 
+mod aligned;
+pub use aligned::AlignedBuf;
+
 mod read;
 pub use read::{Read, ReadAt, ReadAtExt, ReadExt};
 
@@ -8,10 +8,12 @@ use std::{
 /// Seeks to a position in an object.
 pub trait Seek {
     /// A future that resolves to the result of [`Self::seek`].
-    type Seek: Future<Output = Result<u64>>;
+    type Seek<'a>: Future<Output = Result<u64>> + 'a
+    where
+        Self: 'a;
 
     /// Seeks to a given position in this object.
     ///
     /// Returns the new position from the start of this object.
-    fn seek(&mut self, pos: SeekFrom) -> Self::Seek;
+    fn seek(&mut self, pos: SeekFrom) -> Self::Seek<'_>;
 }
@@ -3,5 +3,6 @@
 #![warn(missing_docs, unreachable_pub)]
 #![feature(pin_macro, io_error_more, type_alias_impl_trait)]
 
+pub mod fs;
 pub mod io;
 pub mod net;
@@ -0,0 +1,114 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io::{ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+use tokio::fs;
+
+use super::File;
+use crate::io::{Write, WriteExt};
+
+pub async fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let mut file = AtomicFile::create(path).await?;
+    file.write_all(contents.as_ref()).await?;
+    file.commit().await
+}
+
+#[derive(Debug)]
+pub struct AtomicFile {
+    file: File,
+    path: PathBuf,
+    dir: PathBuf,
+    temp: Option<PathBuf>,
+}
+
+impl AtomicFile {
+    pub async fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file_name = path
+            .file_name()
+            .ok_or(ErrorKind::InvalidFilename)?
+            .to_owned();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_owned(),
+            _ => PathBuf::from("."),
+        };
+        // Keep the permissions of the file being replaced.
+        let perm = match fs::metadata(&path).await {
+            Ok(meta) => Some(meta.permissions()),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        loop {
+            let temp = dir.join(temp_name(&file_name));
+            let res = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp)
+                .await;
+            match res {
+                Ok(file) => {
+                    let atomic = Self {
+                        file: File::from(file),
+                        path,
+                        dir,
+                        temp: Some(temp),
+                    };
+                    if let Some(perm) = perm {
+                        atomic.file.set_permissions(perm).await?;
+                    }
+                    return Ok(atomic);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    pub async fn commit(mut self) -> Result<()> {
+        self.file.sync_all().await?;
+        let temp = self.temp.take().unwrap();
+        if let Err(e) = fs::rename(&temp, &self.path).await {
+            let _ = fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        fs::File::open(&self.dir).await?.sync_all().await
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if let Some(temp) = self.temp.take() {
+            let _ = std::fs::remove_file(temp);
+        }
+    }
+}
+
+impl Write for AtomicFile {
+    type Write<'a> = <File as Write>::Write<'a>;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        self.file.write(buf)
+    }
+}
+
+#[cfg(unix)]
+impl crate::io::WriteAt for AtomicFile {
+    type WriteAt<'a> = <File as crate::io::WriteAt>::WriteAt<'a>;
+
+    fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
+        self.file.write_at(buf, pos)
+    }
+}
+
+fn temp_name(file_name: &OsStr) -> OsString {
+    let mut prefix = OsString::from(".");
+    prefix.push(file_name);
+    prefix.push(".");
+    photonio_base::fs::temp_name(prefix, ".tmp")
+}
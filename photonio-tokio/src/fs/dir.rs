@@ -0,0 +1,260 @@
+use std::{
+    collections::VecDeque,
+    ffi::{CString, OsStr, OsString},
+    future::Future,
+    io::{Error, ErrorKind, Result},
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use tokio::task::{self, JoinHandle};
+
+use super::{asyncify, File, Metadata, OpenOptions, ReadDir};
+
+// Tokio doesn't support operations relative to a directory, so they are
+// performed with the `*at` system calls on the blocking pool instead.
+#[derive(Debug)]
+pub struct Dir(Arc<OwnedFd>);
+
+impl Dir {
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = new_path_str(path.as_ref())?;
+        let fd = asyncify(move || openat(libc::AT_FDCWD, &path, Self::OPEN_FLAGS, 0)).await?;
+        Ok(Self(Arc::new(fd)))
+    }
+
+    pub async fn open_file<P: AsRef<Path>>(&self, path: P, options: &OpenOptions) -> Result<File> {
+        options.open_at(self, path.as_ref()).await
+    }
+
+    pub async fn open_dir<P: AsRef<Path>>(&self, path: P) -> Result<Dir> {
+        let fd = self.open_at(path.as_ref(), Self::OPEN_FLAGS, 0).await?;
+        Ok(Self(Arc::new(fd)))
+    }
+
+    pub async fn create_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = new_path_str(path.as_ref())?;
+        self.with_fd(move |fd| cvt(unsafe { libc::mkdirat(fd, path.as_ptr(), 0o777) }))
+            .await
+    }
+
+    pub async fn remove_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.unlink_at(path.as_ref(), 0).await
+    }
+
+    pub async fn remove_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.unlink_at(path.as_ref(), libc::AT_REMOVEDIR).await
+    }
+
+    pub async fn rename_to<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        from: P,
+        to_dir: &Dir,
+        to: Q,
+    ) -> Result<()> {
+        let from = new_path_str(from.as_ref())?;
+        let to = new_path_str(to.as_ref())?;
+        let to_dir = to_dir.0.clone();
+        self.with_fd(move |fd| {
+            let to_fd = to_dir.as_raw_fd();
+            cvt(unsafe { libc::renameat(fd, from.as_ptr(), to_fd, to.as_ptr()) })
+        })
+        .await
+    }
+
+    pub async fn metadata<P: AsRef<Path>>(&self, path: P) -> Result<Metadata> {
+        stat_at(self.0.clone(), path.as_ref(), 0).await
+    }
+
+    pub async fn read_dir(&self) -> Result<ReadDir> {
+        // Open the directory again, since the position of the stream is shared
+        // by duplicated file descriptors.
+        let fd = self.open_at(Path::new("."), Self::OPEN_FLAGS, 0).await?;
+        Ok(ReadDir::at(ReadDirAt {
+            dir: Arc::new(fd),
+            entries: VecDeque::new(),
+            pending: None,
+            is_eof: false,
+        }))
+    }
+
+    pub async fn sync_all(&self) -> Result<()> {
+        self.with_fd(|fd| cvt(unsafe { libc::fsync(fd) })).await
+    }
+
+    pub(super) async fn open_at(
+        &self,
+        path: &Path,
+        flags: libc::c_int,
+        mode: u32,
+    ) -> Result<OwnedFd> {
+        let path = new_path_str(path)?;
+        self.with_fd(move |fd| openat(fd, &path, flags, mode)).await
+    }
+
+    async fn unlink_at(&self, path: &Path, flags: libc::c_int) -> Result<()> {
+        let path = new_path_str(path)?;
+        self.with_fd(move |fd| cvt(unsafe { libc::unlinkat(fd, path.as_ptr(), flags) }))
+            .await
+    }
+
+    async fn with_fd<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(RawFd) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        // Share the descriptor so that it outlives the blocking function.
+        let fd = self.0.clone();
+        asyncify(move || f(fd.as_raw_fd())).await
+    }
+}
+
+impl Dir {
+    const OPEN_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY;
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+// The entries of a `Dir` are read with `getdents64` on the blocking pool, since
+// tokio can only read directories by path. Their paths are relative to the
+// directory.
+#[derive(Debug)]
+pub(super) struct ReadDirAt {
+    dir: Arc<OwnedFd>,
+    entries: VecDeque<DirEntryAt>,
+    pending: Option<JoinHandle<Result<Vec<DirEntryAt>>>>,
+    is_eof: bool,
+}
+
+impl ReadDirAt {
+    pub(super) fn poll_next_entry(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<DirEntryAt>>> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Poll::Ready(Ok(Some(entry)));
+            }
+            if self.is_eof {
+                return Poll::Ready(Ok(None));
+            }
+            let pending = self.pending.get_or_insert_with(|| {
+                let dir = self.dir.clone();
+                task::spawn_blocking(move || read_entries(dir))
+            });
+            let res = ready!(Pin::new(pending).poll(cx));
+            self.pending = None;
+            let entries = res??;
+            self.is_eof = entries.is_empty();
+            self.entries = entries.into();
+        }
+    }
+}
+
+fn read_entries(dir: Arc<OwnedFd>) -> Result<Vec<DirEntryAt>> {
+    // See `man getdents64.2` for the layout.
+    const RECLEN_OFFSET: usize = 16;
+    const TYPE_OFFSET: usize = 18;
+    const NAME_OFFSET: usize = 19;
+
+    let mut buf = vec![0u8; 8 * 1024];
+    loop {
+        let len = unsafe {
+            libc::syscall(
+                libc::SYS_getdents64,
+                dir.as_raw_fd(),
+                buf.as_mut_ptr(),
+                buf.len(),
+            )
+        };
+        if len < 0 {
+            return Err(Error::last_os_error());
+        }
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos < len as usize {
+            let entry = &buf[pos..];
+            let ino = u64::from_ne_bytes(entry[..8].try_into().unwrap());
+            let reclen = u16::from_ne_bytes(entry[RECLEN_OFFSET..TYPE_OFFSET].try_into().unwrap());
+            let name = &entry[NAME_OFFSET..reclen as usize];
+            let name = &name[..name.iter().position(|&b| b == 0).unwrap()];
+            pos += reclen as usize;
+            if name != b"." && name != b".." {
+                entries.push(DirEntryAt {
+                    dir: dir.clone(),
+                    name: OsStr::from_bytes(name).to_owned(),
+                    ino,
+                });
+            }
+        }
+        // A batch with only `.` and `..` is not the end of the directory.
+        if len == 0 || !entries.is_empty() {
+            return Ok(entries);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct DirEntryAt {
+    dir: Arc<OwnedFd>,
+    name: OsString,
+    ino: u64,
+}
+
+impl DirEntryAt {
+    pub(super) fn path(&self) -> PathBuf {
+        PathBuf::from(&self.name)
+    }
+
+    pub(super) fn file_name(&self) -> OsString {
+        self.name.clone()
+    }
+
+    pub(super) fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    pub(super) async fn metadata(&self) -> Result<Metadata> {
+        stat_at(self.dir.clone(), Path::new(&self.name), libc::O_NOFOLLOW).await
+    }
+}
+
+async fn stat_at(dir: Arc<OwnedFd>, path: &Path, flags: libc::c_int) -> Result<Metadata> {
+    let path = new_path_str(path)?;
+    asyncify(move || {
+        // `O_PATH` opens any kind of file without access to its contents.
+        let fd = openat(dir.as_raw_fd(), &path, libc::O_PATH | flags, 0)?;
+        std::fs::File::from(fd).metadata()
+    })
+    .await
+    .map(Metadata::from)
+}
+
+fn openat(dirfd: RawFd, path: &CString, flags: libc::c_int, mode: u32) -> Result<OwnedFd> {
+    let flags = flags | libc::O_CLOEXEC;
+    let fd = unsafe { libc::openat(dirfd, path.as_ptr(), flags, mode) };
+    cvt(fd)?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn new_path_str(path: &Path) -> Result<CString> {
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::from(ErrorKind::InvalidInput))
+}
+
+fn cvt(ret: libc::c_int) -> Result<()> {
+    if ret >= 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
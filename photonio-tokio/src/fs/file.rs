@@ -1,45 +1,81 @@
-use std::{future::Future, io::Result, path::Path};
+use std::{
+    fs::Permissions,
+    future::Future,
+    io::{Result, SeekFrom},
+    path::Path,
+};
 
 use tokio::{
     fs,
-    io::{AsyncReadExt, AsyncWriteExt},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
 };
 
 use super::Metadata;
-use crate::io::{Read, Write};
+use crate::io::{Read, Seek, Write};
 
 #[derive(Debug)]
-pub struct File(fs::File);
+pub struct File {
+    file: fs::File,
+    // Files opened with `O_DIRECT` remember their alignment, so that
+    // misaligned operations are rejected without querying it each time.
+    #[cfg(target_os = "linux")]
+    direct: Option<DirectAlignment>,
+}
 
 impl File {
     pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        fs::File::open(path).await.map(Self)
+        fs::File::open(path).await.map(Self::from)
     }
 
     pub async fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
-        fs::File::create(path).await.map(Self)
+        fs::File::create(path).await.map(Self::from)
+    }
+
+    pub async fn try_clone(&self) -> Result<Self> {
+        let file = self.file.try_clone().await?;
+        Ok(Self {
+            file,
+            #[cfg(target_os = "linux")]
+            direct: self.direct,
+        })
     }
 
     pub async fn metadata(&self) -> Result<Metadata> {
-        self.0.metadata().await.map(Metadata::from)
+        self.file.metadata().await.map(Metadata::from)
     }
 
     pub async fn set_len(&self, size: u64) -> Result<()> {
-        self.0.set_len(size).await
+        self.file.set_len(size).await
+    }
+
+    pub async fn set_permissions(&self, perm: Permissions) -> Result<()> {
+        self.file.set_permissions(perm).await
     }
 
     pub async fn sync_all(&self) -> Result<()> {
-        self.0.sync_all().await
+        self.file.sync_all().await
     }
 
     pub async fn sync_data(&self) -> Result<()> {
-        self.0.sync_data().await
+        self.file.sync_data().await
     }
 }
 
 impl From<fs::File> for File {
     fn from(file: fs::File) -> Self {
-        Self(file)
+        Self {
+            file,
+            #[cfg(target_os = "linux")]
+            direct: None,
+        }
+    }
+}
+
+impl Seek for File {
+    type Seek<'a> = impl Future<Output = Result<u64>> + 'a;
+
+    fn seek(&mut self, pos: SeekFrom) -> Self::Seek<'_> {
+        self.file.seek(pos)
     }
 }
 
@@ -47,7 +83,13 @@ impl Read for File {
     type Read<'a> = impl Future<Output = Result<usize>> + 'a;
 
     fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
-        self.0.read(buf)
+        async move {
+            #[cfg(target_os = "linux")]
+            if self.direct.is_some() {
+                return self.read_direct(buf).await;
+            }
+            self.file.read(buf).await
+        }
     }
 }
 
@@ -55,7 +97,13 @@ impl Write for File {
     type Write<'a> = impl Future<Output = Result<usize>> + 'a;
 
     fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
-        self.0.write(buf)
+        async move {
+            #[cfg(target_os = "linux")]
+            if self.direct.is_some() {
+                return self.write_direct(buf).await;
+            }
+            self.file.write(buf).await
+        }
     }
 }
 
@@ -72,17 +120,27 @@ mod unix {
     };
 
     use super::File;
-    use crate::io::{ReadAt, WriteAt};
+    use crate::{
+        fs::asyncify,
+        io::{ReadAt, WriteAt},
+    };
+
+    impl File {
+        pub async fn chown(&self, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+            let file = self.file.try_clone().await?.into_std().await;
+            asyncify(move || std::os::unix::fs::fchown(file, uid, gid)).await
+        }
+    }
 
     impl AsRawFd for File {
         fn as_raw_fd(&self) -> RawFd {
-            self.0.as_raw_fd()
+            self.file.as_raw_fd()
         }
     }
 
     impl FromRawFd for File {
         unsafe fn from_raw_fd(fd: RawFd) -> Self {
-            Self(tokio::fs::File::from_raw_fd(fd))
+            Self::from(tokio::fs::File::from_raw_fd(fd))
         }
     }
 
@@ -91,8 +149,13 @@ mod unix {
 
         // FIXME: Make it asynchronous when Tokio supports positional reads.
         fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
-            let file = unsafe { ManuallyDrop::new(std::fs::File::from_raw_fd(self.0.as_raw_fd())) };
-            async move { file.read_at(buf, pos) }
+            let file =
+                unsafe { ManuallyDrop::new(std::fs::File::from_raw_fd(self.file.as_raw_fd())) };
+            async move {
+                #[cfg(target_os = "linux")]
+                self.check_direct(buf, pos)?;
+                file.read_at(buf, pos)
+            }
         }
     }
 
@@ -101,8 +164,328 @@ mod unix {
 
         // FIXME: Make it asynchronous when Tokio supports positional writes.
         fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
-            let file = unsafe { ManuallyDrop::new(std::fs::File::from_raw_fd(self.0.as_raw_fd())) };
-            async move { file.write_at(buf, pos) }
+            let file =
+                unsafe { ManuallyDrop::new(std::fs::File::from_raw_fd(self.file.as_raw_fd())) };
+            async move {
+                #[cfg(target_os = "linux")]
+                self.check_direct(buf, pos)?;
+                file.write_at(buf, pos)
+            }
         }
     }
 }
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        io::{Error, ErrorKind, Result, SeekFrom},
+        mem,
+        ops::BitOr,
+        os::fd::AsRawFd,
+        time::Duration,
+    };
+
+    use tokio::{fs, io::AsyncSeekExt, time};
+
+    use super::File;
+    use crate::{
+        fs::asyncify,
+        io::{ReadAt, WriteAt},
+    };
+
+    impl File {
+        pub async fn allocate(&self, offset: u64, len: u64) -> Result<()> {
+            self.fallocate(0, offset, len).await
+        }
+
+        pub async fn punch_hole(&self, offset: u64, len: u64) -> Result<()> {
+            let mode = libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+            self.fallocate(mode, offset, len).await
+        }
+
+        pub async fn zero_range(&self, offset: u64, len: u64) -> Result<()> {
+            let mode = libc::FALLOC_FL_ZERO_RANGE | libc::FALLOC_FL_KEEP_SIZE;
+            self.fallocate(mode, offset, len).await
+        }
+
+        pub async fn collapse_range(&self, offset: u64, len: u64) -> Result<()> {
+            self.fallocate(libc::FALLOC_FL_COLLAPSE_RANGE, offset, len)
+                .await
+        }
+
+        pub async fn sync_range(&self, offset: u64, len: u64, flags: SyncRangeFlags) -> Result<()> {
+            let offset = to_off(offset)?;
+            let len = to_off(len)?;
+            self.with_std(move |fd| cvt(unsafe { libc::sync_file_range(fd, offset, len, flags.0) }))
+                .await
+        }
+
+        pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> Result<()> {
+            let offset = to_off(offset)?;
+            let len = to_off(len)?;
+            let advice = match advice {
+                Advice::Normal => libc::POSIX_FADV_NORMAL,
+                Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+                Advice::Random => libc::POSIX_FADV_RANDOM,
+                Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+                Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+                Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+            };
+            self.with_std(move |fd| {
+                match unsafe { libc::posix_fadvise64(fd, offset, len, advice) } {
+                    0 => Ok(()),
+                    err => Err(Error::from_raw_os_error(err)),
+                }
+            })
+            .await
+        }
+
+        pub async fn readahead(&self, offset: u64, len: usize) -> Result<()> {
+            let offset = to_off(offset)?;
+            self.with_std(move |fd| cvt(unsafe { libc::readahead(fd, offset, len) } as libc::c_int))
+                .await
+        }
+
+        pub async fn lock_shared(&self) -> Result<()> {
+            self.lock_range(0, 0, LockKind::Shared).await
+        }
+
+        pub async fn lock_exclusive(&self) -> Result<()> {
+            self.lock_range(0, 0, LockKind::Exclusive).await
+        }
+
+        pub fn try_lock_shared(&self) -> Result<()> {
+            self.try_lock_range(0, 0, LockKind::Shared)
+        }
+
+        pub fn try_lock_exclusive(&self) -> Result<()> {
+            self.try_lock_range(0, 0, LockKind::Exclusive)
+        }
+
+        pub fn unlock(&self) -> Result<()> {
+            self.unlock_range(0, 0)
+        }
+
+        // Waiting with `F_OFD_SETLKW` on the blocking pool can't be cancelled,
+        // and the lock could be acquired after the future is dropped. So the
+        // lock is tried again with a backoff instead, like the io_uring backend
+        // does.
+        pub async fn lock_range(&self, offset: u64, len: u64, kind: LockKind) -> Result<()> {
+            let lock = new_flock(kind.to_type(), offset, len)?;
+            let mut backoff = Duration::from_millis(1);
+            loop {
+                match self.fcntl_lock(lock) {
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    res => return res,
+                }
+                time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_millis(64));
+            }
+        }
+
+        pub fn try_lock_range(&self, offset: u64, len: u64, kind: LockKind) -> Result<()> {
+            let lock = new_flock(kind.to_type(), offset, len)?;
+            self.fcntl_lock(lock)
+        }
+
+        pub fn unlock_range(&self, offset: u64, len: u64) -> Result<()> {
+            let lock = new_flock(libc::F_UNLCK, offset, len)?;
+            self.fcntl_lock(lock)
+        }
+
+        pub async fn direct_alignment(&self) -> Result<DirectAlignment> {
+            match self.direct {
+                Some(align) => Ok(align),
+                None => self.with_std(DirectAlignment::from_fd).await,
+            }
+        }
+
+        pub(in crate::fs) fn with_direct(file: fs::File) -> Result<Self> {
+            let fd = file.as_raw_fd();
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            cvt(flags)?;
+            let direct = if flags & libc::O_DIRECT != 0 {
+                Some(DirectAlignment::from_fd(fd)?)
+            } else {
+                None
+            };
+            Ok(Self { file, direct })
+        }
+
+        // Tokio reads and writes through a buffer of its own, which is not
+        // aligned for direct I/O, so the cursor is moved around positional I/O
+        // instead.
+        pub(super) async fn read_direct(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let pos = self.file.stream_position().await?;
+            let n = self.read_at(buf, pos).await?;
+            self.file.seek(SeekFrom::Current(n as i64)).await?;
+            Ok(n)
+        }
+
+        pub(super) async fn write_direct(&mut self, buf: &[u8]) -> Result<usize> {
+            let pos = self.file.stream_position().await?;
+            let n = self.write_at(buf, pos).await?;
+            self.file.seek(SeekFrom::Current(n as i64)).await?;
+            Ok(n)
+        }
+
+        // The kernel accepts some misaligned direct I/O on its own, so this
+        // rejects it the same way as the io_uring backend does.
+        pub(super) fn check_direct(&self, buf: &[u8], pos: u64) -> Result<()> {
+            let Some(align) = self.direct else {
+                return Ok(());
+            };
+            let invalid = |msg: String| Err(Error::new(ErrorKind::InvalidInput, msg));
+            if buf.as_ptr() as usize % align.memory != 0 {
+                return invalid(format!(
+                    "direct I/O buffer address {:p} is not aligned to {} bytes",
+                    buf.as_ptr(),
+                    align.memory
+                ));
+            }
+            if buf.len() % align.offset != 0 {
+                return invalid(format!(
+                    "direct I/O length {} is not a multiple of {} bytes",
+                    buf.len(),
+                    align.offset
+                ));
+            }
+            if pos % align.offset as u64 != 0 {
+                return invalid(format!(
+                    "direct I/O position {} is not a multiple of {} bytes",
+                    pos, align.offset
+                ));
+            }
+            Ok(())
+        }
+
+        async fn fallocate(&self, mode: libc::c_int, offset: u64, len: u64) -> Result<()> {
+            let offset = to_off(offset)?;
+            let len = to_off(len)?;
+            self.with_std(move |fd| cvt(unsafe { libc::fallocate64(fd, mode, offset, len) }))
+                .await
+        }
+
+        fn fcntl_lock(&self, lock: libc::flock) -> Result<()> {
+            let fd = self.file.as_raw_fd();
+            cvt(unsafe { libc::fcntl(fd, libc::F_OFD_SETLK, &lock) })
+        }
+
+        pub(in crate::fs) async fn with_std<F, R>(&self, f: F) -> Result<R>
+        where
+            F: FnOnce(libc::c_int) -> Result<R> + Send + 'static,
+            R: Send + 'static,
+        {
+            // Duplicate the file so that it outlives the blocking function.
+            let file = self.file.try_clone().await?.into_std().await;
+            asyncify(move || f(file.as_raw_fd())).await
+        }
+    }
+
+    fn cvt(ret: libc::c_int) -> Result<()> {
+        if ret >= 0 {
+            Ok(())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+
+    fn to_off(v: u64) -> Result<libc::off64_t> {
+        v.try_into()
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+    }
+
+    fn new_flock(ty: libc::c_int, offset: u64, len: u64) -> Result<libc::flock> {
+        let mut lock: libc::flock = unsafe { mem::zeroed() };
+        lock.l_type = ty as libc::c_short;
+        lock.l_whence = libc::SEEK_SET as libc::c_short;
+        lock.l_start = to_off(offset)?;
+        lock.l_len = to_off(len)?;
+        Ok(lock)
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum LockKind {
+        Shared,
+        Exclusive,
+    }
+
+    impl LockKind {
+        fn to_type(self) -> libc::c_int {
+            match self {
+                Self::Shared => libc::F_RDLCK,
+                Self::Exclusive => libc::F_WRLCK,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Advice {
+        Normal,
+        Sequential,
+        Random,
+        WillNeed,
+        DontNeed,
+        NoReuse,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct DirectAlignment {
+        memory: usize,
+        offset: usize,
+    }
+
+    impl DirectAlignment {
+        pub fn memory(&self) -> usize {
+            self.memory
+        }
+
+        pub fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn from_fd(fd: libc::c_int) -> Result<Self> {
+            let mut stat = unsafe { mem::zeroed() };
+            let path = b"\0".as_ptr().cast();
+            let flags = libc::AT_EMPTY_PATH;
+            cvt(unsafe { libc::statx(fd, path, flags, libc::STATX_DIOALIGN, &mut stat) })?;
+            Self::from_statx(&stat)
+        }
+
+        fn from_statx(stat: &libc::statx) -> Result<Self> {
+            // Old kernels and some filesystems don't report the alignment, in
+            // which case the block size of the filesystem is sufficient.
+            if stat.stx_mask & libc::STATX_DIOALIGN == 0 || stat.stx_dio_mem_align == 0 {
+                let blksize = stat.stx_blksize as usize;
+                return Ok(Self {
+                    memory: blksize,
+                    offset: blksize,
+                });
+            }
+            Ok(Self {
+                memory: stat.stx_dio_mem_align as usize,
+                offset: stat.stx_dio_offset_align as usize,
+            })
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct SyncRangeFlags(libc::c_uint);
+
+    impl SyncRangeFlags {
+        pub const WAIT_BEFORE: Self = Self(libc::SYNC_FILE_RANGE_WAIT_BEFORE);
+        pub const WRITE: Self = Self(libc::SYNC_FILE_RANGE_WRITE);
+        pub const WAIT_AFTER: Self = Self(libc::SYNC_FILE_RANGE_WAIT_AFTER);
+    }
+
+    impl BitOr for SyncRangeFlags {
+        type Output = Self;
+
+        fn bitor(self, rhs: Self) -> Self {
+            Self(self.0 | rhs.0)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::{Advice, DirectAlignment, LockKind, SyncRangeFlags};
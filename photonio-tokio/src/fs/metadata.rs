@@ -1,9 +1,17 @@
-use std::fs;
+use std::{
+    fs::{self, Permissions},
+    io::Result,
+    time::SystemTime,
+};
 
 #[derive(Clone, Debug)]
 pub struct Metadata(fs::Metadata);
 
 impl Metadata {
+    pub fn file_type(&self) -> FileType {
+        FileType(self.0.file_type())
+    }
+
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> u64 {
         self.0.len()
@@ -20,6 +28,22 @@ impl Metadata {
     pub fn is_symlink(&self) -> bool {
         self.0.is_symlink()
     }
+
+    pub fn permissions(&self) -> Permissions {
+        self.0.permissions()
+    }
+
+    pub fn modified(&self) -> Result<SystemTime> {
+        self.0.modified()
+    }
+
+    pub fn accessed(&self) -> Result<SystemTime> {
+        self.0.accessed()
+    }
+
+    pub fn created(&self) -> Result<SystemTime> {
+        self.0.created()
+    }
 }
 
 impl From<fs::Metadata> for Metadata {
@@ -94,3 +118,45 @@ impl std::os::unix::fs::MetadataExt for Metadata {
         self.0.blocks()
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FileType(fs::FileType);
+
+impl FileType {
+    pub fn is_dir(&self) -> bool {
+        self.0.is_dir()
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.0.is_file()
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.0.is_symlink()
+    }
+}
+
+impl From<fs::FileType> for FileType {
+    fn from(file_type: fs::FileType) -> Self {
+        Self(file_type)
+    }
+}
+
+#[cfg(unix)]
+impl std::os::unix::fs::FileTypeExt for FileType {
+    fn is_block_device(&self) -> bool {
+        self.0.is_block_device()
+    }
+
+    fn is_char_device(&self) -> bool {
+        self.0.is_char_device()
+    }
+
+    fn is_fifo(&self) -> bool {
+        self.0.is_fifo()
+    }
+
+    fn is_socket(&self) -> bool {
+        self.0.is_socket()
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt,
+    future::{ready, Future},
+    io::{Error, ErrorKind, Result},
+    ops::{Deref, DerefMut},
+    os::fd::AsRawFd,
+    ptr::{self, NonNull},
+    slice,
+    sync::Arc,
+};
+
+use super::{asyncify, Advice, File};
+use crate::io::ReadAt;
+
+pub struct Mmap(Arc<Mapping>);
+
+impl Mmap {
+    /// # Safety
+    ///
+    /// The mapped range must not be modified or truncated while the map is
+    /// alive.
+    pub async unsafe fn map(file: &File) -> Result<Self> {
+        Mapping::new(file, libc::PROT_READ).await.map(Self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.0.advise(advice)
+    }
+
+    pub async fn lock(&self) -> Result<()> {
+        Mapping::lock(&self.0).await
+    }
+
+    pub fn unlock(&self) -> Result<()> {
+        self.0.unlock()
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl ReadAt for Mmap {
+    type ReadAt<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        ready(Ok(self.0.read_at(buf, pos)))
+    }
+}
+
+impl fmt::Debug for Mmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mmap").field("len", &self.0.len).finish()
+    }
+}
+
+pub struct MmapMut(Arc<Mapping>);
+
+impl MmapMut {
+    /// # Safety
+    ///
+    /// The mapped range must not be accessed otherwise or truncated while the
+    /// map is alive.
+    pub async unsafe fn map_mut(file: &File) -> Result<Self> {
+        Mapping::new(file, libc::PROT_READ | libc::PROT_WRITE)
+            .await
+            .map(Self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    pub async fn flush_async(&self) -> Result<()> {
+        Mapping::blocking(&self.0, |ptr, len| unsafe {
+            libc::msync(ptr, len, libc::MS_SYNC)
+        })
+        .await
+    }
+
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.0.advise(advice)
+    }
+
+    pub async fn lock(&self) -> Result<()> {
+        Mapping::lock(&self.0).await
+    }
+
+    pub fn unlock(&self) -> Result<()> {
+        self.0.unlock()
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl DerefMut for MmapMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.0.ptr.as_ptr(), self.0.len) }
+    }
+}
+
+impl AsRef<[u8]> for MmapMut {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for MmapMut {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl ReadAt for MmapMut {
+    type ReadAt<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        ready(Ok(self.0.read_at(buf, pos)))
+    }
+}
+
+impl fmt::Debug for MmapMut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapMut").field("len", &self.0.len).finish()
+    }
+}
+
+struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    async fn new(file: &File, prot: libc::c_int) -> Result<Arc<Self>> {
+        let len = file.metadata().await?.len();
+        let len = len
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "file is too large to map"))?;
+        if len == 0 {
+            return Ok(Arc::new(Self {
+                ptr: NonNull::dangling(),
+                len,
+            }));
+        }
+        let fd = file.as_raw_fd();
+        let ptr = unsafe { libc::mmap64(ptr::null_mut(), len, prot, libc::MAP_SHARED, fd, 0) };
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+        let ptr = NonNull::new(ptr as *mut u8).unwrap();
+        Ok(Arc::new(Self { ptr, len }))
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    fn read_at(&self, buf: &mut [u8], pos: u64) -> usize {
+        let data = self.as_slice();
+        let start = pos.min(data.len() as u64) as usize;
+        let len = buf.len().min(data.len() - start);
+        buf[..len].copy_from_slice(&data[start..start + len]);
+        len
+    }
+
+    fn advise(&self, advice: Advice) -> Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+            Advice::NoReuse => return Ok(()),
+        };
+        if self.len == 0 {
+            return Ok(());
+        }
+        cvt(unsafe { libc::madvise(self.ptr.as_ptr().cast(), self.len, advice) })
+    }
+
+    async fn lock(this: &Arc<Self>) -> Result<()> {
+        Self::blocking(this, |ptr, len| unsafe { libc::mlock(ptr, len) }).await
+    }
+
+    fn unlock(&self) -> Result<()> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        cvt(unsafe { libc::munlock(self.ptr.as_ptr().cast(), self.len) })
+    }
+
+    // Runs `f` on the blocking pool, which keeps the mapping alive even if the
+    // returned future is dropped.
+    async fn blocking<F>(this: &Arc<Self>, f: F) -> Result<()>
+    where
+        F: FnOnce(*mut libc::c_void, usize) -> libc::c_int + Send + 'static,
+    {
+        if this.len == 0 {
+            return Ok(());
+        }
+        let this = this.clone();
+        asyncify(move || cvt(f(this.ptr.as_ptr().cast(), this.len))).await
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+        }
+    }
+}
+
+fn cvt(ret: libc::c_int) -> Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
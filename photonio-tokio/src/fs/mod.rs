@@ -1,18 +1,173 @@
-use std::{io::Result, path::Path};
+use std::{
+    fs::Permissions,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+};
 
 mod open;
 pub use open::OpenOptions;
 
 mod file;
 pub use file::File;
+#[cfg(target_os = "linux")]
+pub use file::{Advice, DirectAlignment, LockKind, SyncRangeFlags};
+
+#[cfg(target_os = "linux")]
+mod mmap;
+#[cfg(target_os = "linux")]
+pub use mmap::{Mmap, MmapMut};
+
+#[cfg(target_os = "linux")]
+mod watcher;
+#[cfg(target_os = "linux")]
+pub use watcher::{Event, EventPath, WatchDescriptor, Watcher};
+
+#[cfg(target_os = "linux")]
+mod xattr;
+#[cfg(target_os = "linux")]
+pub use xattr::{get_xattr, list_xattr, remove_xattr, set_xattr};
+
+#[cfg(target_os = "linux")]
+mod space;
+#[cfg(target_os = "linux")]
+pub use space::{space, FsStats};
 
 mod metadata;
-pub use metadata::Metadata;
+pub use metadata::{FileType, Metadata};
+
+#[cfg(target_os = "linux")]
+mod dir;
+#[cfg(target_os = "linux")]
+pub use dir::Dir;
+
+mod read_dir;
+pub use read_dir::{read_dir, DirEntry, ReadDir};
+
+mod temp;
+pub use temp::{tempfile, tempfile_in, NamedTempFile, TempDir};
+
+mod atomic;
+pub use atomic::{write_atomic, AtomicFile};
+
+pub async fn metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    tokio::fs::metadata(path).await.map(Metadata::from)
+}
+
+pub async fn symlink_metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    tokio::fs::symlink_metadata(path).await.map(Metadata::from)
+}
+
+pub async fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+pub async fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    tokio::fs::read_to_string(path).await
+}
+
+pub async fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    tokio::fs::write(path, contents).await
+}
+
+pub async fn set_permissions<P: AsRef<Path>>(path: P, perm: Permissions) -> Result<()> {
+    tokio::fs::set_permissions(path, perm).await
+}
+
+#[cfg(unix)]
+pub async fn chown<P: AsRef<Path>>(path: P, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std::os::unix::fs::chown(path, uid, gid)).await
+}
+
+#[cfg(unix)]
+pub async fn lchown<P: AsRef<Path>>(path: P, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std::os::unix::fs::lchown(path, uid, gid)).await
+}
+
+pub async fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<()> {
+    tokio::fs::hard_link(original, link).await
+}
+
+#[cfg(unix)]
+pub async fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<()> {
+    tokio::fs::symlink(original, link).await
+}
+
+pub async fn read_link<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    tokio::fs::read_link(path).await
+}
+
+pub async fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    tokio::fs::canonicalize(path).await
+}
 
 pub async fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     tokio::fs::rename(from, to).await
 }
 
+#[cfg(target_os = "linux")]
+pub async fn rename_noreplace<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    renameat2(from.as_ref(), to.as_ref(), libc::RENAME_NOREPLACE).await
+}
+
+#[cfg(target_os = "linux")]
+pub async fn exchange<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<()> {
+    renameat2(a.as_ref(), b.as_ref(), libc::RENAME_EXCHANGE).await
+}
+
+// Tokio doesn't support `renameat2`.
+#[cfg(target_os = "linux")]
+async fn renameat2(from: &Path, to: &Path, flags: libc::c_uint) -> Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let from = CString::new(from.as_os_str().as_bytes())
+        .map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+    let to = CString::new(to.as_os_str().as_bytes())
+        .map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+    asyncify(move || {
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_renameat2,
+                libc::AT_FDCWD,
+                from.as_ptr(),
+                libc::AT_FDCWD,
+                to.as_ptr(),
+                flags,
+            )
+        };
+        if ret < 0 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    })
+    .await
+}
+
+// Other platforms emulate this with a hard link, which fails if `to` exists.
+// Directories are not supported.
+#[cfg(not(target_os = "linux"))]
+pub async fn rename_noreplace<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let from = from.as_ref();
+    if tokio::fs::symlink_metadata(from).await?.is_dir() {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "rename_noreplace is not supported for directories",
+        ));
+    }
+    tokio::fs::hard_link(from, to).await?;
+    tokio::fs::remove_file(from).await
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn exchange<P: AsRef<Path>, Q: AsRef<Path>>(_: P, _: Q) -> Result<()> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "exchange is not supported on this platform",
+    ))
+}
+
 pub async fn remove_file<P: AsRef<Path>>(path: P) -> Result<()> {
     tokio::fs::remove_file(path).await
 }
@@ -21,6 +176,26 @@ pub async fn create_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     tokio::fs::create_dir(path).await
 }
 
+pub async fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    tokio::fs::create_dir_all(path).await
+}
+
 pub async fn remove_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     tokio::fs::remove_dir(path).await
 }
+
+pub async fn remove_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    tokio::fs::remove_dir_all(path).await
+}
+
+pub async fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u64> {
+    tokio::fs::copy(from, to).await
+}
+
+async fn asyncify<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await?
+}
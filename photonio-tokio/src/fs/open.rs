@@ -4,45 +4,72 @@ use tokio::fs;
 
 use super::File;
 
-pub struct OpenOptions(fs::OpenOptions);
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct OpenOptions {
+    options: fs::OpenOptions,
+    // The options are recorded to open files relative to a directory, which
+    // Tokio doesn't support.
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    #[cfg(unix)]
+    mode: u32,
+    #[cfg(unix)]
+    custom_flags: i32,
+    #[cfg(target_os = "linux")]
+    direct: bool,
+}
 
 impl OpenOptions {
     pub fn new() -> Self {
-        Self(fs::OpenOptions::new())
+        Self::from(fs::OpenOptions::new())
     }
 
     pub fn read(&mut self, read: bool) -> &mut Self {
-        self.0.read(read);
+        self.options.read(read);
+        self.read = read;
         self
     }
 
     pub fn write(&mut self, write: bool) -> &mut Self {
-        self.0.write(write);
+        self.options.write(write);
+        self.write = write;
         self
     }
 
     pub fn append(&mut self, append: bool) -> &mut Self {
-        self.0.append(append);
+        self.options.append(append);
+        self.append = append;
         self
     }
 
     pub fn truncate(&mut self, truncate: bool) -> &mut Self {
-        self.0.truncate(truncate);
+        self.options.truncate(truncate);
+        self.truncate = truncate;
         self
     }
 
     pub fn create(&mut self, create: bool) -> &mut Self {
-        self.0.create(create);
+        self.options.create(create);
+        self.create = create;
         self
     }
 
     pub fn create_new(&mut self, create_new: bool) -> &mut Self {
-        self.0.create_new(create_new);
+        self.options.create_new(create_new);
+        self.create_new = create_new;
         self
     }
 
     pub async fn open<P: AsRef<Path>>(&self, path: P) -> Result<File> {
-        self.0.open(path).await.map(File::from)
+        let file = self.options.open(path).await?;
+        #[cfg(target_os = "linux")]
+        return File::with_direct(file);
+        #[cfg(not(target_os = "linux"))]
+        Ok(File::from(file))
     }
 }
 
@@ -52,21 +79,89 @@ impl Default for OpenOptions {
     }
 }
 
+// Options set on `fs::OpenOptions` are not recorded, so `Dir::open_file` only
+// sees the options set afterwards.
 impl From<fs::OpenOptions> for OpenOptions {
     fn from(options: fs::OpenOptions) -> Self {
-        Self(options)
+        Self {
+            options,
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            #[cfg(unix)]
+            mode: 0o666,
+            #[cfg(unix)]
+            custom_flags: 0,
+            #[cfg(target_os = "linux")]
+            direct: false,
+        }
     }
 }
 
 #[cfg(unix)]
 impl std::os::unix::fs::OpenOptionsExt for OpenOptions {
     fn mode(&mut self, mode: u32) -> &mut Self {
-        self.0.mode(mode);
+        self.options.mode(mode);
+        self.mode = mode;
         self
     }
 
     fn custom_flags(&mut self, flags: i32) -> &mut Self {
-        self.0.custom_flags(flags);
+        self.custom_flags = flags;
+        self.options.custom_flags(self.flags());
         self
     }
 }
+
+#[cfg(target_os = "linux")]
+impl OpenOptions {
+    pub fn direct(&mut self, direct: bool) -> &mut Self {
+        self.direct = direct;
+        self.options.custom_flags(self.flags());
+        self
+    }
+}
+
+#[cfg(unix)]
+impl OpenOptions {
+    fn flags(&self) -> i32 {
+        #[cfg(target_os = "linux")]
+        if self.direct {
+            return self.custom_flags | libc::O_DIRECT;
+        }
+        self.custom_flags
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl OpenOptions {
+    pub(super) async fn open_at(&self, dir: &super::Dir, path: &Path) -> Result<File> {
+        let fd = dir.open_at(path, self.open_flags(), self.mode).await?;
+        File::with_direct(fs::File::from_std(fd.into()))
+    }
+
+    fn open_flags(&self) -> libc::c_int {
+        let mut flags = match (self.read, self.write, self.append) {
+            (true, _, true) => libc::O_RDWR | libc::O_APPEND,
+            (true, true, false) => libc::O_RDWR,
+            (true, false, false) => libc::O_RDONLY,
+            (false, _, true) => libc::O_WRONLY | libc::O_APPEND,
+            (false, true, false) => libc::O_WRONLY,
+            (false, false, false) => 0,
+        };
+        if self.create_new {
+            flags |= libc::O_CREAT | libc::O_EXCL;
+        } else {
+            if self.create {
+                flags |= libc::O_CREAT;
+            }
+            if self.truncate {
+                flags |= libc::O_TRUNC;
+            }
+        }
+        flags | (self.flags() & !libc::O_ACCMODE)
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    ffi::OsString,
+    future::poll_fn,
+    io::Result,
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use tokio::fs;
+
+#[cfg(target_os = "linux")]
+use super::dir::{DirEntryAt, ReadDirAt};
+use super::{FileType, Metadata};
+
+pub async fn read_dir<P: AsRef<Path>>(path: P) -> Result<ReadDir> {
+    fs::read_dir(path)
+        .await
+        .map(|dir| ReadDir(Inner::Path(dir)))
+}
+
+#[derive(Debug)]
+pub struct ReadDir(Inner);
+
+#[derive(Debug)]
+enum Inner {
+    Path(fs::ReadDir),
+    #[cfg(target_os = "linux")]
+    At(ReadDirAt),
+}
+
+impl ReadDir {
+    #[cfg(target_os = "linux")]
+    pub(super) fn at(dir: ReadDirAt) -> Self {
+        Self(Inner::At(dir))
+    }
+
+    pub async fn next_entry(&mut self) -> Result<Option<DirEntry>> {
+        poll_fn(|cx| self.poll_next_entry(cx)).await
+    }
+
+    pub fn poll_next_entry(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<DirEntry>>> {
+        match &mut self.0 {
+            Inner::Path(dir) => dir
+                .poll_next_entry(cx)
+                .map(|res| res.map(|e| e.map(|e| DirEntry(EntryInner::Path(e))))),
+            #[cfg(target_os = "linux")]
+            Inner::At(dir) => dir
+                .poll_next_entry(cx)
+                .map(|res| res.map(|e| e.map(|e| DirEntry(EntryInner::At(e))))),
+        }
+    }
+}
+
+impl Stream for ReadDir {
+    type Item = Result<DirEntry>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_entry(cx).map(Result::transpose)
+    }
+}
+
+#[derive(Debug)]
+pub struct DirEntry(EntryInner);
+
+#[derive(Debug)]
+enum EntryInner {
+    Path(fs::DirEntry),
+    #[cfg(target_os = "linux")]
+    At(DirEntryAt),
+}
+
+impl DirEntry {
+    pub fn path(&self) -> PathBuf {
+        match &self.0 {
+            EntryInner::Path(e) => e.path(),
+            #[cfg(target_os = "linux")]
+            EntryInner::At(e) => e.path(),
+        }
+    }
+
+    pub fn file_name(&self) -> OsString {
+        match &self.0 {
+            EntryInner::Path(e) => e.file_name(),
+            #[cfg(target_os = "linux")]
+            EntryInner::At(e) => e.file_name(),
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn ino(&self) -> u64 {
+        match &self.0 {
+            EntryInner::Path(e) => e.ino(),
+            #[cfg(target_os = "linux")]
+            EntryInner::At(e) => e.ino(),
+        }
+    }
+
+    pub async fn metadata(&self) -> Result<Metadata> {
+        match &self.0 {
+            EntryInner::Path(e) => e.metadata().await.map(Metadata::from),
+            #[cfg(target_os = "linux")]
+            EntryInner::At(e) => e.metadata().await,
+        }
+    }
+
+    pub async fn file_type(&self) -> Result<FileType> {
+        match &self.0 {
+            EntryInner::Path(e) => e.file_type().await.map(FileType::from),
+            #[cfg(target_os = "linux")]
+            EntryInner::At(e) => e.metadata().await.map(|m| m.file_type()),
+        }
+    }
+}
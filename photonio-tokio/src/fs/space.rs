@@ -0,0 +1,94 @@
+use std::{
+    ffi::CString,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem,
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+use super::{asyncify, File};
+
+pub async fn space<P: AsRef<Path>>(path: P) -> Result<FsStats> {
+    let path = CString::new(path.as_ref().as_os_str().as_bytes())
+        .map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+    asyncify(move || {
+        let mut stat = unsafe { mem::zeroed() };
+        cvt(unsafe { libc::statfs64(path.as_ptr(), &mut stat) })?;
+        Ok(FsStats(stat))
+    })
+    .await
+}
+
+impl File {
+    pub async fn space(&self) -> Result<FsStats> {
+        self.with_std(|fd| {
+            let mut stat = unsafe { mem::zeroed() };
+            cvt(unsafe { libc::fstatfs64(fd, &mut stat) })?;
+            Ok(FsStats(stat))
+        })
+        .await
+    }
+}
+
+#[derive(Clone)]
+pub struct FsStats(libc::statfs64);
+
+impl FsStats {
+    pub fn total_bytes(&self) -> u64 {
+        self.0.f_blocks * self.fragment_size()
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.0.f_bfree * self.fragment_size()
+    }
+
+    pub fn available_bytes(&self) -> u64 {
+        self.0.f_bavail * self.fragment_size()
+    }
+
+    pub fn total_inodes(&self) -> u64 {
+        self.0.f_files
+    }
+
+    pub fn free_inodes(&self) -> u64 {
+        self.0.f_ffree
+    }
+
+    pub fn block_size(&self) -> u64 {
+        self.0.f_bsize as u64
+    }
+
+    pub fn fs_type(&self) -> u64 {
+        self.0.f_type as u64
+    }
+
+    fn fragment_size(&self) -> u64 {
+        match self.0.f_frsize {
+            0 => self.0.f_bsize as u64,
+            size => size as u64,
+        }
+    }
+}
+
+impl fmt::Debug for FsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsStats")
+            .field("total_bytes", &self.total_bytes())
+            .field("free_bytes", &self.free_bytes())
+            .field("available_bytes", &self.available_bytes())
+            .field("total_inodes", &self.total_inodes())
+            .field("free_inodes", &self.free_inodes())
+            .field("block_size", &self.block_size())
+            .field("fs_type", &format_args!("{:#x}", self.fs_type()))
+            .finish()
+    }
+}
+
+fn cvt(ret: libc::c_int) -> Result<()> {
+    if ret >= 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
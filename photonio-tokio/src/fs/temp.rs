@@ -0,0 +1,182 @@
+use std::{
+    io::{ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+use photonio_base::fs::temp_name;
+use tokio::fs;
+
+use super::{File, OpenOptions};
+use crate::io::{Read, Write};
+
+pub async fn tempfile() -> Result<File> {
+    tempfile_in(std::env::temp_dir()).await
+}
+
+pub async fn tempfile_in<P: AsRef<Path>>(dir: P) -> Result<File> {
+    let dir = dir.as_ref();
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let res = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .mode(0o600)
+            .custom_flags(libc::O_TMPFILE)
+            .open(dir)
+            .await;
+        match res {
+            Ok(file) => return Ok(file),
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EOPNOTSUPP | libc::EISDIR)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let (file, path) = create_named(dir).await?;
+    fs::remove_file(&path).await?;
+    Ok(file)
+}
+
+#[derive(Debug)]
+pub struct NamedTempFile {
+    file: File,
+    path: TempPath,
+}
+
+impl NamedTempFile {
+    pub async fn new() -> Result<Self> {
+        Self::new_in(std::env::temp_dir()).await
+    }
+
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let (file, path) = create_named(dir.as_ref()).await?;
+        Ok(Self {
+            file,
+            path: TempPath(Some(path)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.0.as_deref().unwrap()
+    }
+
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    pub async fn persist<P: AsRef<Path>>(self, path: P) -> Result<File> {
+        let Self {
+            file,
+            path: mut guard,
+        } = self;
+        let temp = guard.0.take().unwrap();
+        if let Err(e) = fs::rename(&temp, path).await {
+            let _ = fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        Ok(file)
+    }
+}
+
+#[derive(Debug)]
+struct TempPath(Option<PathBuf>);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Read for NamedTempFile {
+    type Read<'a> = <File as Read>::Read<'a>;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        self.file.read(buf)
+    }
+}
+
+#[cfg(unix)]
+impl crate::io::ReadAt for NamedTempFile {
+    type ReadAt<'a> = <File as crate::io::ReadAt>::ReadAt<'a>;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        self.file.read_at(buf, pos)
+    }
+}
+
+impl Write for NamedTempFile {
+    type Write<'a> = <File as Write>::Write<'a>;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        self.file.write(buf)
+    }
+}
+
+#[cfg(unix)]
+impl crate::io::WriteAt for NamedTempFile {
+    type WriteAt<'a> = <File as crate::io::WriteAt>::WriteAt<'a>;
+
+    fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
+        self.file.write_at(buf, pos)
+    }
+}
+
+#[derive(Debug)]
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    pub async fn new() -> Result<Self> {
+        Self::new_in(std::env::temp_dir()).await
+    }
+
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name(".tmp", ""));
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            builder.mode(0o700);
+            match builder.create(&path).await {
+                Ok(()) => return Ok(Self { path: Some(path) }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap()
+    }
+
+    pub async fn close(mut self) -> Result<()> {
+        let path = self.path.take().unwrap();
+        fs::remove_dir_all(path).await
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = std::fs::remove_dir_all(path);
+        }
+    }
+}
+
+async fn create_named(dir: &Path) -> Result<(File, PathBuf)> {
+    loop {
+        let path = dir.join(temp_name(".tmp", ""));
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path).await {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    ffi::{CString, OsStr, OsString},
+    fmt,
+    future::poll_fn,
+    io::{Error, ErrorKind, Result},
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    },
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use futures::Stream;
+use tokio::io::unix::AsyncFd;
+
+use super::asyncify;
+
+pub struct Watcher {
+    fd: Arc<AsyncFd<OwnedFd>>,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Watcher {
+    const BUF_SIZE: usize = 4096;
+
+    const MASK: u32 = libc::IN_CREATE
+        | libc::IN_MODIFY
+        | libc::IN_DELETE
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    pub fn new() -> Result<Self> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        Ok(Self {
+            fd: Arc::new(AsyncFd::new(fd)?),
+            buf: Vec::with_capacity(Self::BUF_SIZE),
+            pos: 0,
+        })
+    }
+
+    pub async fn add<P: AsRef<Path>>(&self, path: P) -> Result<WatchDescriptor> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| Error::from(ErrorKind::InvalidFilename))?;
+        let fd = self.fd.clone();
+        asyncify(move || {
+            let wd = unsafe { libc::inotify_add_watch(fd.as_raw_fd(), path.as_ptr(), Self::MASK) };
+            if wd < 0 {
+                Err(Error::last_os_error())
+            } else {
+                Ok(WatchDescriptor(wd))
+            }
+        })
+        .await
+    }
+
+    pub fn remove(&self, wd: WatchDescriptor) -> Result<()> {
+        if unsafe { libc::inotify_rm_watch(self.fd.as_raw_fd(), wd.0) } < 0 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn next_event(&mut self) -> Result<Event> {
+        poll_fn(|cx| self.poll_next_event(cx)).await
+    }
+
+    pub fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Result<Event>> {
+        loop {
+            if let Some(event) = self.next_buffered_event() {
+                return Poll::Ready(Ok(event));
+            }
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            let buf = &mut self.buf;
+            buf.resize(Self::BUF_SIZE, 0);
+            let res = guard.try_io(|fd| {
+                let ret = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+                if ret < 0 {
+                    Err(Error::last_os_error())
+                } else {
+                    Ok(ret as usize)
+                }
+            });
+            match res {
+                Ok(Ok(len)) => {
+                    buf.truncate(len);
+                    self.pos = 0;
+                }
+                Ok(Err(err)) => {
+                    buf.clear();
+                    return Poll::Ready(Err(err));
+                }
+                Err(_) => buf.clear(),
+            }
+        }
+    }
+
+    fn next_buffered_event(&mut self) -> Option<Event> {
+        while self.pos < self.buf.len() {
+            let (raw, len) = RawEvent::parse(&self.buf[self.pos..]);
+            self.pos += len;
+            if raw.mask & libc::IN_MOVED_FROM != 0 && self.pos < self.buf.len() {
+                let (next, next_len) = RawEvent::parse(&self.buf[self.pos..]);
+                if next.mask & libc::IN_MOVED_TO != 0 && next.cookie == raw.cookie {
+                    self.pos += next_len;
+                    return Some(Event::Move {
+                        from: raw.into_path(),
+                        to: next.into_path(),
+                    });
+                }
+            }
+            if let Some(event) = raw.into_event() {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+impl Stream for Watcher {
+    type Item = Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_event(cx).map(Some)
+    }
+}
+
+impl fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Watcher").field(self.fd.get_ref()).finish()
+    }
+}
+
+impl AsRawFd for Watcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchDescriptor(libc::c_int);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventPath {
+    wd: WatchDescriptor,
+    name: Option<OsString>,
+}
+
+impl EventPath {
+    pub fn watch(&self) -> WatchDescriptor {
+        self.wd
+    }
+
+    pub fn name(&self) -> Option<&OsStr> {
+        self.name.as_deref()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Create(EventPath),
+    Modify(EventPath),
+    Delete(EventPath),
+    Move { from: EventPath, to: EventPath },
+    MovedFrom { path: EventPath, cookie: u32 },
+    MovedTo { path: EventPath, cookie: u32 },
+    Removed(WatchDescriptor),
+    Overflow,
+}
+
+struct RawEvent {
+    wd: libc::c_int,
+    mask: u32,
+    cookie: u32,
+    name: Option<OsString>,
+}
+
+impl RawEvent {
+    fn parse(buf: &[u8]) -> (Self, usize) {
+        const HEADER_LEN: usize = 16;
+
+        let field = |i: usize| u32::from_ne_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        let len = field(3) as usize;
+        let name = &buf[HEADER_LEN..HEADER_LEN + len];
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(len)];
+        let event = Self {
+            wd: field(0) as libc::c_int,
+            mask: field(1),
+            cookie: field(2),
+            name: (!name.is_empty()).then(|| OsStr::from_bytes(name).to_owned()),
+        };
+        (event, HEADER_LEN + len)
+    }
+
+    fn into_path(self) -> EventPath {
+        EventPath {
+            wd: WatchDescriptor(self.wd),
+            name: self.name,
+        }
+    }
+
+    fn into_event(self) -> Option<Event> {
+        let mask = self.mask;
+        let cookie = self.cookie;
+        let event = if mask & libc::IN_Q_OVERFLOW != 0 {
+            Event::Overflow
+        } else if mask & libc::IN_IGNORED != 0 {
+            Event::Removed(WatchDescriptor(self.wd))
+        } else if mask & libc::IN_CREATE != 0 {
+            Event::Create(self.into_path())
+        } else if mask & libc::IN_MODIFY != 0 {
+            Event::Modify(self.into_path())
+        } else if mask & (libc::IN_DELETE | libc::IN_DELETE_SELF) != 0 {
+            Event::Delete(self.into_path())
+        } else if mask & libc::IN_MOVED_FROM != 0 {
+            Event::MovedFrom {
+                path: self.into_path(),
+                cookie,
+            }
+        } else if mask & libc::IN_MOVED_TO != 0 {
+            Event::MovedTo {
+                path: self.into_path(),
+                cookie,
+            }
+        } else {
+            return None;
+        };
+        Some(event)
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    ffi::{CString, OsStr, OsString},
+    io::{Error, ErrorKind, Result},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+use super::{asyncify, File};
+
+pub async fn get_xattr<P, N>(path: P, name: N) -> Result<Option<Vec<u8>>>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    let path = new_cstr(path.as_ref().as_os_str())?;
+    let name = new_cstr(name.as_ref())?;
+    asyncify(move || get(Target::Path(&path), &name)).await
+}
+
+pub async fn set_xattr<P, N>(path: P, name: N, value: &[u8]) -> Result<()>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    let path = new_cstr(path.as_ref().as_os_str())?;
+    let name = new_cstr(name.as_ref())?;
+    let value = value.to_owned();
+    asyncify(move || set(Target::Path(&path), &name, &value)).await
+}
+
+pub async fn remove_xattr<P, N>(path: P, name: N) -> Result<()>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    let path = new_cstr(path.as_ref().as_os_str())?;
+    let name = new_cstr(name.as_ref())?;
+    asyncify(move || remove(Target::Path(&path), &name)).await
+}
+
+pub async fn list_xattr<P: AsRef<Path>>(path: P) -> Result<Vec<OsString>> {
+    let path = new_cstr(path.as_ref().as_os_str())?;
+    asyncify(move || list(Target::Path(&path))).await
+}
+
+impl File {
+    pub async fn get_xattr<N: AsRef<OsStr>>(&self, name: N) -> Result<Option<Vec<u8>>> {
+        let name = new_cstr(name.as_ref())?;
+        self.with_std(move |fd| get(Target::Fd(fd), &name)).await
+    }
+
+    pub async fn set_xattr<N: AsRef<OsStr>>(&self, name: N, value: &[u8]) -> Result<()> {
+        let name = new_cstr(name.as_ref())?;
+        let value = value.to_owned();
+        self.with_std(move |fd| set(Target::Fd(fd), &name, &value))
+            .await
+    }
+
+    pub async fn remove_xattr<N: AsRef<OsStr>>(&self, name: N) -> Result<()> {
+        let name = new_cstr(name.as_ref())?;
+        self.with_std(move |fd| remove(Target::Fd(fd), &name)).await
+    }
+
+    pub async fn list_xattr(&self) -> Result<Vec<OsString>> {
+        self.with_std(|fd| list(Target::Fd(fd))).await
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Target<'a> {
+    Fd(libc::c_int),
+    Path(&'a CString),
+}
+
+const BUF_SIZE: usize = 256;
+
+fn get(target: Target<'_>, name: &CString) -> Result<Option<Vec<u8>>> {
+    let mut buf = vec![0u8; BUF_SIZE];
+    loop {
+        let ptr = buf.as_mut_ptr().cast();
+        let ret = match target {
+            Target::Fd(fd) => unsafe { libc::fgetxattr(fd, name.as_ptr(), ptr, buf.len()) },
+            Target::Path(path) => unsafe {
+                libc::getxattr(path.as_ptr(), name.as_ptr(), ptr, buf.len())
+            },
+        };
+        match cvt(ret) {
+            Ok(len) => {
+                buf.truncate(len);
+                return Ok(Some(buf));
+            }
+            Err(err) if err.raw_os_error() == Some(libc::ENODATA) => return Ok(None),
+            Err(err) if err.raw_os_error() == Some(libc::ERANGE) => {
+                buf.resize(buf.len() * 2, 0);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn set(target: Target<'_>, name: &CString, value: &[u8]) -> Result<()> {
+    let ptr = value.as_ptr().cast();
+    let ret = match target {
+        Target::Fd(fd) => unsafe { libc::fsetxattr(fd, name.as_ptr(), ptr, value.len(), 0) },
+        Target::Path(path) => unsafe {
+            libc::setxattr(path.as_ptr(), name.as_ptr(), ptr, value.len(), 0)
+        },
+    };
+    cvt(ret as isize).map(drop)
+}
+
+fn remove(target: Target<'_>, name: &CString) -> Result<()> {
+    let ret = match target {
+        Target::Fd(fd) => unsafe { libc::fremovexattr(fd, name.as_ptr()) },
+        Target::Path(path) => unsafe { libc::removexattr(path.as_ptr(), name.as_ptr()) },
+    };
+    cvt(ret as isize).map(drop)
+}
+
+fn list(target: Target<'_>) -> Result<Vec<OsString>> {
+    let mut buf = vec![0u8; BUF_SIZE];
+    let len = loop {
+        let ptr = buf.as_mut_ptr().cast();
+        let ret = match target {
+            Target::Fd(fd) => unsafe { libc::flistxattr(fd, ptr, buf.len()) },
+            Target::Path(path) => unsafe { libc::listxattr(path.as_ptr(), ptr, buf.len()) },
+        };
+        match cvt(ret) {
+            Ok(len) => break len,
+            Err(err) if err.raw_os_error() == Some(libc::ERANGE) => {
+                buf.resize(buf.len() * 2, 0);
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    let names = buf[..len]
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| OsStr::from_bytes(name).to_owned())
+        .collect();
+    Ok(names)
+}
+
+fn new_cstr(s: &OsStr) -> Result<CString> {
+    CString::new(s.as_bytes()).map_err(|_| Error::from(ErrorKind::InvalidInput))
+}
+
+fn cvt(ret: isize) -> Result<usize> {
+    if ret >= 0 {
+        Ok(ret as usize)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
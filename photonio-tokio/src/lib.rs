@@ -2,9 +2,16 @@
 
 #![warn(unreachable_pub)]
 #![feature(pin_macro, io_error_more, type_alias_impl_trait)]
+#![cfg_attr(unix, feature(unix_chown))]
 
 pub mod fs;
 pub mod io;
 pub mod net;
+pub mod process;
 pub mod runtime;
 pub mod task;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use tokio;
+}
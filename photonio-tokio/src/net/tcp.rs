@@ -43,6 +43,15 @@ impl TcpStream {
         Ok(Self(net::TcpStream::connect(addr).await?))
     }
 
+    #[cfg(unix)]
+    pub fn try_clone(&self) -> Result<Self> {
+        use std::os::unix::io::AsFd;
+
+        // The duplicate shares the non-blocking mode of the socket.
+        let fd = self.0.as_fd().try_clone_to_owned()?;
+        net::TcpStream::from_std(fd.into()).map(Self)
+    }
+
     pub fn local_addr(&self) -> Result<SocketAddr> {
         self.0.local_addr()
     }
@@ -0,0 +1,111 @@
+use std::{
+    future::Future,
+    io::{ErrorKind, Result},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    process,
+};
+
+use super::{ExitStatus, Output};
+use crate::io::{Read, Write};
+
+#[derive(Debug)]
+pub struct Child {
+    pid: u32,
+    child: process::Child,
+    pub stdin: Option<ChildStdin>,
+    pub stdout: Option<ChildStdout>,
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    pub fn id(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn kill(&mut self) -> Result<()> {
+        match self.child.start_kill() {
+            // The child has been reaped.
+            Err(e) if e.kind() == ErrorKind::InvalidInput => Ok(()),
+            res => res,
+        }
+    }
+
+    pub async fn wait(&mut self) -> Result<ExitStatus> {
+        drop(self.stdin.take());
+        self.child.wait().await
+    }
+
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    pub async fn wait_with_output(mut self) -> Result<Output> {
+        drop(self.stdin.take());
+        let (stdout, stderr) = futures::join!(
+            read_to_end(self.stdout.take().map(|stdout| stdout.0)),
+            read_to_end(self.stderr.take().map(|stderr| stderr.0))
+        );
+        let status = self.wait().await?;
+        Ok(Output {
+            status,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+}
+
+impl From<process::Child> for Child {
+    fn from(mut child: process::Child) -> Self {
+        Self {
+            pid: child.id().unwrap(),
+            stdin: child.stdin.take().map(ChildStdin),
+            stdout: child.stdout.take().map(ChildStdout),
+            stderr: child.stderr.take().map(ChildStderr),
+            child,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChildStdin(process::ChildStdin);
+
+impl Write for ChildStdin {
+    type Write<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        self.0.write(buf)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChildStdout(process::ChildStdout);
+
+impl Read for ChildStdout {
+    type Read<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        self.0.read(buf)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChildStderr(process::ChildStderr);
+
+impl Read for ChildStderr {
+    type Read<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        self.0.read(buf)
+    }
+}
+
+async fn read_to_end<R: AsyncReadExt + Unpin>(reader: Option<R>) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Some(mut reader) = reader {
+        reader.read_to_end(&mut buf).await?;
+    }
+    Ok(buf)
+}
@@ -0,0 +1,133 @@
+use std::{ffi::OsStr, io::Result, path::Path};
+
+use tokio::process;
+
+use super::{Child, ExitStatus, Output, Stdio};
+
+#[derive(Debug)]
+pub struct Command {
+    std: process::Command,
+    has_stdin: bool,
+    has_stdout: bool,
+    has_stderr: bool,
+}
+
+impl Command {
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Self {
+        Self::from(std::process::Command::new(program))
+    }
+
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.std.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.env(key, val);
+        self
+    }
+
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.envs(vars);
+        self
+    }
+
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Self {
+        self.std.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.std.env_clear();
+        self
+    }
+
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.std.current_dir(dir);
+        self
+    }
+
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stdin(cfg);
+        self.has_stdin = true;
+        self
+    }
+
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stdout(cfg);
+        self.has_stdout = true;
+        self
+    }
+
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stderr(cfg);
+        self.has_stderr = true;
+        self
+    }
+
+    pub fn spawn(&mut self) -> Result<Child> {
+        self.std.spawn().map(Child::from)
+    }
+
+    pub async fn output(&mut self) -> Result<Output> {
+        self.spawn_output()?.wait_with_output().await
+    }
+
+    pub async fn status(&mut self) -> Result<ExitStatus> {
+        self.std.status().await
+    }
+}
+
+impl Command {
+    fn spawn_output(&mut self) -> Result<Child> {
+        if !self.has_stdin {
+            self.std.stdin(Stdio::null());
+        }
+        if !self.has_stdout {
+            self.std.stdout(Stdio::piped());
+        }
+        if !self.has_stderr {
+            self.std.stderr(Stdio::piped());
+        }
+        let child = self.spawn();
+        if !self.has_stdin {
+            self.std.stdin(Stdio::inherit());
+        }
+        if !self.has_stdout {
+            self.std.stdout(Stdio::inherit());
+        }
+        if !self.has_stderr {
+            self.std.stderr(Stdio::inherit());
+        }
+        child
+    }
+}
+
+impl From<std::process::Command> for Command {
+    fn from(command: std::process::Command) -> Self {
+        Self {
+            std: process::Command::from(command),
+            has_stdin: false,
+            has_stdout: false,
+            has_stderr: false,
+        }
+    }
+}
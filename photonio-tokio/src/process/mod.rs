@@ -0,0 +1,7 @@
+pub use std::process::{ExitStatus, Output, Stdio};
+
+mod command;
+pub use command::Command;
+
+mod child;
+pub use child::{Child, ChildStderr, ChildStdin, ChildStdout};
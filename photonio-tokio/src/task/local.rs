@@ -0,0 +1,94 @@
+use std::{error::Error, fmt, future::Future};
+
+use tokio::task::{self, futures::TaskLocalFuture};
+
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            $crate::__private::tokio::task_local! {
+                static __KEY: $t;
+            }
+            $crate::task::LocalKey { inner: &__KEY }
+        };
+    };
+}
+
+// Tokio doesn't export the error of `try_with`, so the key is wrapped to
+// return an error that can be named.
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: &'static task::LocalKey<T>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        self.inner.scope(value, future)
+    }
+
+    pub fn sync_scope<F, R>(&'static self, value: T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.inner.sync_scope(value, f)
+    }
+
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner.with(f)
+    }
+
+    pub fn try_with<F, R>(&'static self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner.try_with(f).map_err(|_| AccessError(()))
+    }
+}
+
+impl<T: Clone + 'static> LocalKey<T> {
+    pub fn get(&'static self) -> T {
+        self.inner.get()
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("LocalKey { .. }")
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct AccessError(());
+
+impl fmt::Debug for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessError").finish()
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
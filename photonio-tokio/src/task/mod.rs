@@ -4,6 +4,10 @@ use tokio::task;
 
 mod join;
 pub use join::{JoinError, JoinHandle};
+pub use task::{futures::TaskLocalFuture, unconstrained, Unconstrained};
+
+mod local;
+pub use local::{AccessError, LocalKey};
 
 #[derive(Debug)]
 pub struct Task(TaskId);
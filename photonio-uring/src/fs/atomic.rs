@@ -0,0 +1,177 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io::{ErrorKind, Result},
+    os::unix::{
+        fs::OpenOptionsExt,
+        io::{AsFd, AsRawFd},
+    },
+    path::{Path, PathBuf},
+};
+
+use super::{File, OpenOptions};
+use crate::{
+    io::{Write, WriteAt, WriteExt},
+    runtime::syscall,
+};
+
+/// Writes `contents` to a file atomically.
+///
+/// The file either keeps its old contents or has the new contents, even if the
+/// system crashes in the middle of the write.
+///
+/// See also [`AtomicFile`].
+pub async fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let mut file = AtomicFile::create(path).await?;
+    file.write_all(contents.as_ref()).await?;
+    file.commit().await
+}
+
+/// A file that replaces another file atomically.
+///
+/// The data is written to a temporary file in the same directory as the
+/// target. When [`Self::commit`] is called, the temporary file is synchronized
+/// to disk and renamed to the target, and then the directory is synchronized
+/// too, so the rename survives a crash.
+///
+/// If the filesystem supports `O_TMPFILE` and `/proc` is mounted, the temporary
+/// file is unnamed until it is committed, so nothing is left behind if the
+/// process crashes. Otherwise, a hidden file named after the target is used,
+/// which is removed if this is dropped without committing.
+///
+/// If the target exists, the temporary file gets the same permissions.
+#[derive(Debug)]
+pub struct AtomicFile {
+    file: File,
+    path: PathBuf,
+    dir: PathBuf,
+    temp: Option<PathBuf>,
+}
+
+impl AtomicFile {
+    /// Creates a temporary file to replace the file at `path`.
+    pub async fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file_name = path
+            .file_name()
+            .ok_or(ErrorKind::InvalidFilename)?
+            .to_owned();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_owned(),
+            _ => PathBuf::from("."),
+        };
+
+        // Keep the permissions of the file being replaced.
+        let mode = match syscall::stat(&path, 0).await {
+            Ok(stat) => Some(libc::mode_t::from(stat.stx_mode) & 0o7777),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let (file, temp) = create_temp(&dir, &file_name).await?;
+        let atomic = Self {
+            file,
+            path,
+            dir,
+            temp,
+        };
+        if let Some(mode) = mode {
+            syscall::fchmod(atomic.file.as_fd(), mode).await?;
+        }
+        Ok(atomic)
+    }
+
+    /// Returns the temporary file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Replaces the target file with the temporary file.
+    pub async fn commit(mut self) -> Result<()> {
+        self.file.sync_all().await?;
+        let temp = match self.temp.take() {
+            Some(temp) => temp,
+            None => {
+                // Give the unnamed file a name first, since `linkat` can't
+                // replace an existing file.
+                let file_name = self.path.file_name().unwrap();
+                let temp = self.dir.join(temp_name(file_name));
+                let proc_path = format!("/proc/self/fd/{}", self.file.as_raw_fd());
+                syscall::link(Path::new(&proc_path), &temp, libc::AT_SYMLINK_FOLLOW).await?;
+                temp
+            }
+        };
+        if let Err(e) = syscall::rename(&temp, &self.path, 0).await {
+            let _ = syscall::unlink(&temp).await;
+            return Err(e);
+        }
+        let dir = File::open(&self.dir).await?;
+        syscall::fsync(dir.as_fd()).await
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if let Some(temp) = self.temp.take() {
+            let _ = std::fs::remove_file(temp);
+        }
+    }
+}
+
+impl Write for AtomicFile {
+    type Write<'a> = <File as Write>::Write<'a>;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        self.file.write(buf)
+    }
+}
+
+impl WriteAt for AtomicFile {
+    type WriteAt<'a> = <File as WriteAt>::WriteAt<'a>;
+
+    fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
+        self.file.write_at(buf, pos)
+    }
+}
+
+/// Creates a temporary file in `dir`.
+///
+/// Returns the path of the file if it is named.
+async fn create_temp(dir: &Path, file_name: &OsStr) -> Result<(File, Option<PathBuf>)> {
+    // An unnamed file can only be linked to the directory through `/proc`.
+    if syscall::stat(Path::new("/proc/self/fd"), 0).await.is_ok() {
+        let res = OpenOptions::new()
+            .write(true)
+            .mode(0o666)
+            .custom_flags(libc::O_TMPFILE)
+            .open(dir)
+            .await;
+        match res {
+            Ok(file) => return Ok((file, None)),
+            // The filesystem doesn't support `O_TMPFILE`.
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EOPNOTSUPP | libc::EISDIR)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    loop {
+        let temp = dir.join(temp_name(file_name));
+        let res = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o666)
+            .open(&temp)
+            .await;
+        match res {
+            Ok(file) => return Ok((file, Some(temp))),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Returns a random name for the temporary file of `file_name`.
+fn temp_name(file_name: &OsStr) -> OsString {
+    let mut prefix = OsString::from(".");
+    prefix.push(file_name);
+    prefix.push(".");
+    photonio_base::fs::temp_name(prefix, ".tmp")
+}
@@ -0,0 +1,136 @@
+use std::{
+    io::Result,
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    path::Path,
+};
+
+use super::{File, Metadata, OpenOptions, ReadDir};
+use crate::runtime::syscall;
+
+/// A reference to an open directory.
+///
+/// Paths given to the methods of this type are resolved relative to the
+/// directory, so they keep referring to the same entries even if the directory
+/// is renamed. Absolute paths are resolved as usual.
+#[derive(Debug)]
+pub struct Dir(OwnedFd);
+
+impl Dir {
+    /// Opens the directory at `path`.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        syscall::open(path, Self::OPEN_FLAGS, 0).await.map(Self)
+    }
+
+    /// Opens a file relative to this directory with the given options.
+    ///
+    /// See also [`OpenOptions::open`].
+    pub async fn open_file<P: AsRef<Path>>(&self, path: P, options: &OpenOptions) -> Result<File> {
+        let path = path.as_ref();
+        options.open_at(self.as_fd(), path).await
+    }
+
+    /// Opens a directory relative to this directory.
+    pub async fn open_dir<P: AsRef<Path>>(&self, path: P) -> Result<Dir> {
+        let path = path.as_ref();
+        syscall::openat(self.as_fd(), path, Self::OPEN_FLAGS, 0)
+            .await
+            .map(Self)
+    }
+
+    /// Creates a directory relative to this directory.
+    ///
+    /// See also [`super::create_dir`].
+    pub async fn create_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        syscall::mkdirat(self.as_fd(), path, 0o777).await
+    }
+
+    /// Removes a file relative to this directory.
+    ///
+    /// See also [`super::remove_file`].
+    pub async fn remove_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        syscall::unlinkat(self.as_fd(), path, 0).await
+    }
+
+    /// Removes an empty directory relative to this directory.
+    ///
+    /// See also [`super::remove_dir`].
+    pub async fn remove_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        syscall::unlinkat(self.as_fd(), path, libc::AT_REMOVEDIR).await
+    }
+
+    /// Renames `from` relative to this directory to `to` relative to
+    /// `to_dir`.
+    ///
+    /// See also [`super::rename`].
+    pub async fn rename_to<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        from: P,
+        to_dir: &Dir,
+        to: Q,
+    ) -> Result<()> {
+        let from = from.as_ref();
+        let to = to.as_ref();
+        syscall::renameat(self.as_fd(), from, to_dir.as_fd(), to, 0).await
+    }
+
+    /// Returns the metadata of a file relative to this directory.
+    ///
+    /// Use `.` as the path to get the metadata of this directory.
+    ///
+    /// See also [`super::metadata`].
+    pub async fn metadata<P: AsRef<Path>>(&self, path: P) -> Result<Metadata> {
+        let path = path.as_ref();
+        syscall::statx(self.as_fd(), path, 0)
+            .await
+            .map(Metadata::from)
+    }
+
+    /// Returns a stream over the entries within this directory.
+    ///
+    /// The paths of the entries are relative to this directory, and their
+    /// metadata is queried relative to it too, so the entries stay valid even
+    /// if the directory is renamed.
+    ///
+    /// See also [`super::read_dir`].
+    pub async fn read_dir(&self) -> Result<ReadDir> {
+        // Open the directory again, since the position of the stream is shared
+        // by duplicated file descriptors.
+        let path = Path::new(".");
+        let dir = syscall::openat(self.as_fd(), path, Self::OPEN_FLAGS, 0).await?;
+        Ok(ReadDir::new(dir, None))
+    }
+
+    /// Synchronizes the entries of this directory to disk.
+    ///
+    /// See also [`File::sync_all`].
+    pub async fn sync_all(&self) -> Result<()> {
+        syscall::fsync(self.as_fd()).await
+    }
+}
+
+impl Dir {
+    const OPEN_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY;
+}
+
+#[doc(hidden)]
+impl AsFd for Dir {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Dir {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self(OwnedFd::from_raw_fd(fd))
+    }
+}
@@ -1,9 +1,17 @@
 use std::{
-    future::{ready, Future},
-    io::{Error, ErrorKind, Result, Seek as _},
-    mem::ManuallyDrop,
-    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    fs::Permissions,
+    future::Future,
+    io::{Error, ErrorKind, Result},
+    ops::BitOr,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+        unix::fs::PermissionsExt,
+    },
     path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use super::{Metadata, OpenOptions};
@@ -14,9 +22,21 @@ use crate::{
 
 /// A reference to an open file.
 ///
+/// Each handle tracks a cursor for [`Read`], [`Write`], and [`Seek`], which is
+/// shared with handles created by [`File::try_clone`], like the cursor of the
+/// kernel is shared by [`std::fs::File::try_clone`]. Files opened in append
+/// mode and files that can't seek, like pipes, use the cursor of the kernel
+/// instead.
+///
 /// This type is an async version of [`std::fs::File`].
 #[derive(Debug)]
-pub struct File(OwnedFd);
+pub struct File {
+    fd: OwnedFd,
+    direct: Option<DirectAlignment>,
+    /// The cursor shared by this handle and its clones, or `None` if the
+    /// cursor of the kernel is used.
+    cursor: Option<Arc<AtomicU64>>,
+}
 
 impl File {
     /// Opens a file in read-only mode.
@@ -42,6 +62,22 @@ impl File {
             .await
     }
 
+    /// Creates a new handle to the same open file.
+    ///
+    /// Both handles share the cursor, status flags and locks of the file, so
+    /// reading from one handle moves the cursor of the other. Reading from both
+    /// handles at the same time may read the same data twice.
+    ///
+    /// See also [`std::fs::File::try_clone`].
+    pub async fn try_clone(&self) -> Result<Self> {
+        let fd = self.fd.try_clone()?;
+        Ok(Self {
+            fd,
+            direct: self.direct,
+            cursor: self.cursor.clone(),
+        })
+    }
+
     /// Returns the metadata about this file.
     ///
     /// See also [`std::fs::File::metadata`].
@@ -53,7 +89,66 @@ impl File {
     ///
     /// See also [`std::fs::File::set_len`].
     pub async fn set_len(&self, size: u64) -> Result<()> {
-        self.as_std(|file| file.set_len(size))
+        syscall::ftruncate(self.as_fd(), to_off(size)?).await
+    }
+
+    /// Allocates disk space for the given range of this file.
+    ///
+    /// The file size is extended if the range is beyond the end of the file.
+    /// Subsequent writes to the range won't fail for lack of space.
+    ///
+    /// See also `man fallocate.2`.
+    pub async fn allocate(&self, offset: u64, len: u64) -> Result<()> {
+        self.fallocate(0, offset, len).await
+    }
+
+    /// Deallocates disk space for the given range of this file.
+    ///
+    /// The range reads as zeros afterwards. The file size is not changed.
+    ///
+    /// See also `FALLOC_FL_PUNCH_HOLE` in `man fallocate.2`.
+    pub async fn punch_hole(&self, offset: u64, len: u64) -> Result<()> {
+        let mode = libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+        self.fallocate(mode, offset, len).await
+    }
+
+    /// Zeroes the given range of this file.
+    ///
+    /// Unlike writing zeros, this is usually done by converting the range to
+    /// unwritten extents. The file size is not changed.
+    ///
+    /// See also `FALLOC_FL_ZERO_RANGE` in `man fallocate.2`.
+    pub async fn zero_range(&self, offset: u64, len: u64) -> Result<()> {
+        let mode = libc::FALLOC_FL_ZERO_RANGE | libc::FALLOC_FL_KEEP_SIZE;
+        self.fallocate(mode, offset, len).await
+    }
+
+    /// Removes the given range from this file without leaving a hole.
+    ///
+    /// The data after the range is moved down to `offset`, and the file size
+    /// is reduced by `len`. Both `offset` and `len` must be multiples of the
+    /// filesystem block size.
+    ///
+    /// See also `FALLOC_FL_COLLAPSE_RANGE` in `man fallocate.2`.
+    pub async fn collapse_range(&self, offset: u64, len: u64) -> Result<()> {
+        self.fallocate(libc::FALLOC_FL_COLLAPSE_RANGE, offset, len)
+            .await
+    }
+
+    /// Changes the permissions of this file.
+    ///
+    /// See also [`std::fs::File::set_permissions`].
+    pub async fn set_permissions(&self, perm: Permissions) -> Result<()> {
+        syscall::fchmod(self.as_fd(), perm.mode()).await
+    }
+
+    /// Changes the owner and group of this file.
+    ///
+    /// If `uid` or `gid` is `None`, the corresponding id is not changed.
+    ///
+    /// See also [`std::os::unix::fs::fchown`].
+    pub async fn chown(&self, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+        syscall::fchown(self.as_fd(), super::to_id(uid), super::to_id(gid)).await
     }
 
     /// Synchronizes all modified data of this file to disk.
@@ -70,51 +165,422 @@ impl File {
     pub async fn sync_data(&self) -> Result<()> {
         syscall::fdatasync(self.as_fd()).await
     }
+
+    /// Controls the write-back of the given range of this file.
+    ///
+    /// If `len` is zero, the range extends to the end of the file. Unlike
+    /// [`Self::sync_data`], this function doesn't flush metadata or the disk
+    /// cache, so it doesn't guarantee durability.
+    ///
+    /// See also `man sync_file_range.2`.
+    pub async fn sync_range(&self, offset: u64, len: u64, flags: SyncRangeFlags) -> Result<()> {
+        // The length of the operation is limited to 32 bits.
+        const MAX_LEN: u64 = 1 << 30;
+        let end = offset.checked_add(len).ok_or(ErrorKind::InvalidInput)?;
+        let mut offset = offset;
+        loop {
+            let chunk = if len == 0 {
+                0
+            } else {
+                (end - offset).min(MAX_LEN)
+            };
+            syscall::sync_file_range(self.as_fd(), to_off(offset)?, chunk as u32, flags.0).await?;
+            offset += chunk;
+            if offset >= end {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl File {
+    /// Declares an access pattern for the given range of this file.
+    ///
+    /// If `len` is zero, the range extends to the end of the file.
+    ///
+    /// See also `man posix_fadvise.2`.
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+            Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+        };
+        syscall::fadvise(self.as_fd(), to_off(offset)?, to_off(len)?, advice).await
+    }
+
+    /// Reads the given range of this file into the page cache.
+    ///
+    /// See also `man readahead.2`.
+    pub async fn readahead(&self, offset: u64, len: usize) -> Result<()> {
+        syscall::readahead(self.as_fd(), to_off(offset)?, len).await
+    }
+}
+
+impl File {
+    /// Acquires a shared lock on this file, waiting until it is available.
+    ///
+    /// See [`Self::lock_range`] for details.
+    pub async fn lock_shared(&self) -> Result<()> {
+        self.lock_range(0, 0, LockKind::Shared).await
+    }
+
+    /// Acquires an exclusive lock on this file, waiting until it is available.
+    ///
+    /// See [`Self::lock_range`] for details.
+    pub async fn lock_exclusive(&self) -> Result<()> {
+        self.lock_range(0, 0, LockKind::Exclusive).await
+    }
+
+    /// Tries to acquire a shared lock on this file.
+    ///
+    /// See [`Self::try_lock_range`] for details.
+    pub fn try_lock_shared(&self) -> Result<()> {
+        self.try_lock_range(0, 0, LockKind::Shared)
+    }
+
+    /// Tries to acquire an exclusive lock on this file.
+    ///
+    /// See [`Self::try_lock_range`] for details.
+    pub fn try_lock_exclusive(&self) -> Result<()> {
+        self.try_lock_range(0, 0, LockKind::Exclusive)
+    }
+
+    /// Releases the locks held on this file.
+    pub fn unlock(&self) -> Result<()> {
+        self.unlock_range(0, 0)
+    }
+
+    /// Acquires a lock on the given range of this file, waiting until it is
+    /// available.
+    ///
+    /// If `len` is zero, the range extends to the end of the file, including
+    /// any data appended later. An existing lock held by this file on the range
+    /// is converted to the new kind.
+    ///
+    /// The lock is an open file description lock, which is owned by this file
+    /// rather than the process. Conflicting locks are held by files opened
+    /// separately, even within the same process. The lock is released by
+    /// [`Self::unlock_range`] or when the file is closed.
+    ///
+    /// While the lock is held elsewhere, it is tried again with a backoff of
+    /// up to 64 ms, so dropping the returned future cancels the wait.
+    ///
+    /// See also `F_OFD_SETLKW` in `man fcntl.2`.
+    pub async fn lock_range(&self, offset: u64, len: u64, kind: LockKind) -> Result<()> {
+        let lock = new_flock(kind.to_type(), offset, len)?;
+        syscall::ofd_lock(self.as_fd(), lock).await
+    }
+
+    /// Tries to acquire a lock on the given range of this file.
+    ///
+    /// Returns an error of kind [`ErrorKind::WouldBlock`] if a conflicting lock
+    /// is held. This function never blocks.
+    ///
+    /// See [`Self::lock_range`] for details.
+    pub fn try_lock_range(&self, offset: u64, len: u64, kind: LockKind) -> Result<()> {
+        let lock = new_flock(kind.to_type(), offset, len)?;
+        syscall::try_ofd_lock(self.as_fd(), lock)
+    }
+
+    /// Releases the locks held on the given range of this file.
+    ///
+    /// If `len` is zero, the range extends to the end of the file.
+    pub fn unlock_range(&self, offset: u64, len: u64) -> Result<()> {
+        let lock = new_flock(libc::F_UNLCK, offset, len)?;
+        syscall::try_ofd_lock(self.as_fd(), lock)
+    }
 }
 
 impl File {
-    fn as_std<F, R>(&self, f: F) -> R
-    where
-        F: Fn(&mut std::fs::File) -> R,
-    {
-        // Convert the file to a `std::fs::File` without taking its ownership.
-        let fd = self.0.as_raw_fd();
-        let mut file = unsafe { ManuallyDrop::new(std::fs::File::from_raw_fd(fd)) };
-        f(&mut file)
+    /// Returns the alignment that direct I/O requires for this file.
+    ///
+    /// Buffers for direct I/O can be allocated with
+    /// [`AlignedBuf`](crate::io::AlignedBuf) to satisfy the alignment.
+    ///
+    /// See also `STATX_DIOALIGN` in `man statx.2`.
+    pub async fn direct_alignment(&self) -> Result<DirectAlignment> {
+        match self.direct {
+            Some(align) => Ok(align),
+            None => DirectAlignment::query(self.as_fd()).await,
+        }
+    }
+}
+
+impl File {
+    /// Creates a file from a descriptor opened with `flags`.
+    ///
+    /// Files opened with `O_DIRECT` remember their alignment, so that
+    /// misaligned operations are rejected before submission.
+    pub(super) async fn with_flags(fd: OwnedFd, flags: libc::c_int) -> Result<Self> {
+        let direct = if flags & libc::O_DIRECT != 0 {
+            Some(DirectAlignment::query(fd.as_fd()).await?)
+        } else {
+            None
+        };
+        Ok(Self::new(fd, direct, flags & libc::O_APPEND != 0))
+    }
+
+    fn new(fd: OwnedFd, direct: Option<DirectAlignment>, append: bool) -> Self {
+        // Writes in append mode always go to the end of the file, so they
+        // can't use a cursor of their own.
+        let pos = if append {
+            None
+        } else {
+            // Start from the cursor of the kernel, which fails if the file
+            // can't seek.
+            syscall::lseek(fd.as_fd(), 0, libc::SEEK_CUR).ok()
+        };
+        let cursor = pos.map(|pos| Arc::new(AtomicU64::new(pos)));
+        Self { fd, direct, cursor }
+    }
+
+    fn pos(&self) -> Option<u64> {
+        self.cursor.as_ref().map(|c| c.load(Ordering::Relaxed))
+    }
+
+    fn set_pos(&self, pos: u64) {
+        if let Some(cursor) = &self.cursor {
+            cursor.store(pos, Ordering::Relaxed);
+        }
+    }
+
+    /// Reads at `pos`, polling for the completion if this file uses direct
+    /// I/O and the runtime uses IOPOLL.
+    async fn pread(&self, buf: &mut [u8], pos: libc::off64_t) -> Result<usize> {
+        if self.direct.is_some() {
+            syscall::pread_direct(self.fd.as_fd(), buf, pos).await
+        } else {
+            syscall::pread(self.fd.as_fd(), buf, pos).await
+        }
+    }
+
+    /// Writes at `pos`, polling for the completion if this file uses direct
+    /// I/O and the runtime uses IOPOLL.
+    async fn pwrite(&self, buf: &[u8], pos: libc::off64_t) -> Result<usize> {
+        if self.direct.is_some() {
+            syscall::pwrite_direct(self.fd.as_fd(), buf, pos).await
+        } else {
+            syscall::pwrite(self.fd.as_fd(), buf, pos).await
+        }
+    }
+
+    /// Checks that an operation on `buf` at `pos` satisfies the alignment of
+    /// direct I/O, if this file uses it.
+    fn check_direct(&self, buf: &[u8], pos: Option<u64>) -> Result<()> {
+        let Some(align) = self.direct else {
+            return Ok(());
+        };
+        let invalid = |msg: String| Err(Error::new(ErrorKind::InvalidInput, msg));
+        if buf.as_ptr() as usize % align.memory != 0 {
+            return invalid(format!(
+                "direct I/O buffer address {:p} is not aligned to {} bytes",
+                buf.as_ptr(),
+                align.memory
+            ));
+        }
+        if buf.len() % align.offset != 0 {
+            return invalid(format!(
+                "direct I/O length {} is not a multiple of {} bytes",
+                buf.len(),
+                align.offset
+            ));
+        }
+        match pos {
+            Some(pos) if pos % align.offset as u64 != 0 => invalid(format!(
+                "direct I/O position {} is not a multiple of {} bytes",
+                pos, align.offset
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn fallocate(&self, mode: libc::c_int, offset: u64, len: u64) -> Result<()> {
+        syscall::fallocate(self.as_fd(), mode, to_off(offset)?, to_off(len)?).await
+    }
+}
+
+fn to_whence(pos: SeekFrom) -> (libc::off64_t, libc::c_int) {
+    match pos {
+        SeekFrom::Start(n) => (n as libc::off64_t, libc::SEEK_SET),
+        SeekFrom::End(n) => (n, libc::SEEK_END),
+        SeekFrom::Current(n) => (n, libc::SEEK_CUR),
+    }
+}
+
+fn add_signed(base: u64, offset: i64) -> Result<u64> {
+    let pos = if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.unsigned_abs())
+    };
+    pos.ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
+fn to_off(v: u64) -> Result<libc::off64_t> {
+    v.try_into()
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+}
+
+fn new_flock(ty: libc::c_int, offset: u64, len: u64) -> Result<libc::flock> {
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = ty as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    lock.l_start = to_off(offset)?;
+    lock.l_len = to_off(len)?;
+    Ok(lock)
+}
+
+/// Kinds of locks for [`File::lock_range`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockKind {
+    /// A shared lock, which can be held by multiple files at the same time.
+    Shared,
+    /// An exclusive lock, which can be held by only one file at a time.
+    Exclusive,
+}
+
+impl LockKind {
+    fn to_type(self) -> libc::c_int {
+        match self {
+            Self::Shared => libc::F_RDLCK,
+            Self::Exclusive => libc::F_WRLCK,
+        }
+    }
+}
+
+/// Access patterns for [`File::advise`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Advice {
+    /// No special treatment. This is the default.
+    Normal,
+    /// The data will be accessed sequentially.
+    Sequential,
+    /// The data will be accessed in random order.
+    Random,
+    /// The data will be accessed in the near future.
+    WillNeed,
+    /// The data will not be accessed in the near future.
+    ///
+    /// Clean pages of the data are dropped from the page cache.
+    DontNeed,
+    /// The data will be accessed only once.
+    NoReuse,
+}
+
+/// Flags for [`File::sync_range`].
+///
+/// See also `man sync_file_range.2`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncRangeFlags(libc::c_uint);
+
+impl SyncRangeFlags {
+    /// Waits for write-back of pages in the range that is already in progress.
+    pub const WAIT_BEFORE: Self = Self(libc::SYNC_FILE_RANGE_WAIT_BEFORE);
+    /// Starts write-back of dirty pages in the range.
+    pub const WRITE: Self = Self(libc::SYNC_FILE_RANGE_WRITE);
+    /// Waits for write-back of pages in the range to complete.
+    pub const WAIT_AFTER: Self = Self(libc::SYNC_FILE_RANGE_WAIT_AFTER);
+}
+
+impl BitOr for SyncRangeFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// The alignment that direct I/O requires for a file.
+///
+/// See also `STATX_DIOALIGN` in `man statx.2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirectAlignment {
+    memory: usize,
+    offset: usize,
+}
+
+impl DirectAlignment {
+    /// Returns the alignment in bytes of the memory of buffers.
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Returns the alignment in bytes of file offsets and lengths.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    async fn query(fd: BorrowedFd<'_>) -> Result<Self> {
+        let stat = syscall::fstatx(fd, libc::STATX_DIOALIGN).await?;
+        if stat.stx_mask & libc::STATX_DIOALIGN == 0 || stat.stx_dio_mem_align == 0 {
+            // The kernel is older than 6.1, or the filesystem doesn't report
+            // the alignment. The block size of the filesystem is a multiple of
+            // the logical block size of the device, so it is always sufficient.
+            let blksize = stat.stx_blksize as usize;
+            return Ok(Self {
+                memory: blksize,
+                offset: blksize,
+            });
+        }
+        Ok(Self {
+            memory: stat.stx_dio_mem_align as usize,
+            offset: stat.stx_dio_offset_align as usize,
+        })
     }
 }
 
 #[doc(hidden)]
 impl From<OwnedFd> for File {
     fn from(fd: OwnedFd) -> Self {
-        Self(fd)
+        let flags = syscall::get_status_flags(fd.as_fd()).unwrap_or(0);
+        Self::new(fd, None, flags & libc::O_APPEND != 0)
     }
 }
 
 #[doc(hidden)]
 impl AsFd for File {
     fn as_fd(&self) -> BorrowedFd<'_> {
-        self.0.as_fd()
+        self.fd.as_fd()
     }
 }
 
 impl AsRawFd for File {
     fn as_raw_fd(&self) -> RawFd {
-        self.0.as_raw_fd()
+        self.fd.as_raw_fd()
     }
 }
 
 impl FromRawFd for File {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
-        Self(OwnedFd::from_raw_fd(fd))
+        Self::from(OwnedFd::from_raw_fd(fd))
     }
 }
 
 impl Seek for File {
-    type Seek = impl Future<Output = Result<u64>>;
+    type Seek<'a> = impl Future<Output = Result<u64>> + 'a;
 
-    fn seek(&mut self, pos: SeekFrom) -> Self::Seek {
-        ready(self.as_std(|file| file.seek(pos)))
+    fn seek(&mut self, pos: SeekFrom) -> Self::Seek<'_> {
+        async move {
+            let Some(cur) = self.pos() else {
+                let (offset, whence) = to_whence(pos);
+                return syscall::lseek(self.as_fd(), offset, whence);
+            };
+            let pos = match pos {
+                SeekFrom::Start(n) => n,
+                SeekFrom::End(n) => add_signed(self.metadata().await?.len(), n)?,
+                SeekFrom::Current(n) => add_signed(cur, n)?,
+            };
+            self.set_pos(pos);
+            Ok(pos)
+        }
     }
 }
 
@@ -122,7 +588,16 @@ impl Read for File {
     type Read<'a> = impl Future<Output = Result<usize>> + 'a;
 
     fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
-        syscall::read(self.0.as_fd(), buf)
+        async move {
+            let pos = self.pos();
+            self.check_direct(buf, pos)?;
+            let Some(pos) = pos else {
+                return syscall::read(self.fd.as_fd(), buf).await;
+            };
+            let n = self.pread(buf, to_off(pos)?).await?;
+            self.set_pos(pos + n as u64);
+            Ok(n)
+        }
     }
 }
 
@@ -131,10 +606,11 @@ impl ReadAt for File {
 
     fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
         async move {
+            self.check_direct(buf, Some(pos))?;
             let pos = pos
                 .try_into()
                 .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-            syscall::pread(self.0.as_fd(), buf, pos).await
+            self.pread(buf, pos).await
         }
     }
 }
@@ -143,7 +619,16 @@ impl Write for File {
     type Write<'a> = impl Future<Output = Result<usize>> + 'a;
 
     fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
-        syscall::write(self.0.as_fd(), buf)
+        async move {
+            let pos = self.pos();
+            self.check_direct(buf, pos)?;
+            let Some(pos) = pos else {
+                return syscall::write(self.fd.as_fd(), buf).await;
+            };
+            let n = self.pwrite(buf, to_off(pos)?).await?;
+            self.set_pos(pos + n as u64);
+            Ok(n)
+        }
     }
 }
 
@@ -152,10 +637,11 @@ impl WriteAt for File {
 
     fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
         async move {
+            self.check_direct(buf, Some(pos))?;
             let pos = pos
                 .try_into()
                 .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-            syscall::pwrite(self.0.as_fd(), buf, pos).await
+            self.pwrite(buf, pos).await
         }
     }
 }
@@ -1,4 +1,10 @@
-use std::fmt;
+use std::{
+    fmt,
+    fs::Permissions,
+    io::{Error, ErrorKind, Result},
+    os::unix::fs::PermissionsExt,
+    time::{Duration, SystemTime},
+};
 
 /// Metadata information about a file.
 ///
@@ -7,6 +13,13 @@ use std::fmt;
 pub struct Metadata(libc::statx);
 
 impl Metadata {
+    /// Returns the file type for this metadata.
+    ///
+    /// See also [`std::fs::Metadata::file_type`].
+    pub fn file_type(&self) -> FileType {
+        FileType::from_mode(self.0.stx_mode.into())
+    }
+
     /// Returns the size of the file this metadata is for.
     ///
     /// See also [`std::fs::Metadata::len`].
@@ -35,11 +48,60 @@ impl Metadata {
     pub fn is_symlink(&self) -> bool {
         self.is_type(libc::S_IFLNK)
     }
+
+    /// Returns the permissions of the file this metadata is for.
+    ///
+    /// See also [`std::fs::Metadata::permissions`].
+    pub fn permissions(&self) -> Permissions {
+        Permissions::from_mode(self.0.stx_mode.into())
+    }
+
+    /// Returns the last modification time of this metadata.
+    ///
+    /// See also [`std::fs::Metadata::modified`].
+    pub fn modified(&self) -> Result<SystemTime> {
+        Ok(to_system_time(&self.0.stx_mtime))
+    }
+
+    /// Returns the last access time of this metadata.
+    ///
+    /// See also [`std::fs::Metadata::accessed`].
+    pub fn accessed(&self) -> Result<SystemTime> {
+        Ok(to_system_time(&self.0.stx_atime))
+    }
+
+    /// Returns the creation time of this metadata.
+    ///
+    /// Returns an error if the filesystem doesn't record the creation time.
+    ///
+    /// See also [`std::fs::Metadata::created`].
+    pub fn created(&self) -> Result<SystemTime> {
+        if self.0.stx_mask & libc::STATX_BTIME != 0 {
+            Ok(to_system_time(&self.0.stx_btime))
+        } else {
+            Err(Error::new(
+                ErrorKind::Unsupported,
+                "creation time is not available for the filesystem",
+            ))
+        }
+    }
 }
 
 impl Metadata {
     fn is_type(&self, ty: libc::mode_t) -> bool {
-        (self.0.stx_mode as u32 & libc::S_IFMT) == ty
+        self.file_type().is(ty)
+    }
+}
+
+fn to_system_time(ts: &libc::statx_timestamp) -> SystemTime {
+    // The nanoseconds are added to the seconds, even if the seconds are
+    // negative.
+    let secs = Duration::from_secs(ts.tv_sec.unsigned_abs());
+    let nsecs = Duration::from_nanos(ts.tv_nsec.into());
+    if ts.tv_sec >= 0 {
+        SystemTime::UNIX_EPOCH + secs + nsecs
+    } else {
+        SystemTime::UNIX_EPOCH - secs + nsecs
     }
 }
 
@@ -53,10 +115,12 @@ impl From<libc::statx> for Metadata {
 impl fmt::Debug for Metadata {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Metadata")
+            .field("file_type", &self.file_type())
+            .field("permissions", &self.permissions())
             .field("len", &self.len())
-            .field("is_dir", &self.is_dir())
-            .field("is_file", &self.is_file())
-            .field("is_symlink", &self.is_symlink())
+            .field("modified", &self.modified())
+            .field("accessed", &self.accessed())
+            .field("created", &self.created())
             .finish()
     }
 }
@@ -126,3 +190,85 @@ impl std::os::unix::fs::MetadataExt for Metadata {
         self.0.stx_blocks
     }
 }
+
+/// A structure representing a type of file.
+///
+/// See also [`std::fs::FileType`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FileType(libc::mode_t);
+
+impl FileType {
+    /// Returns true if this file type is a directory.
+    ///
+    /// See also [`std::fs::FileType::is_dir`].
+    pub fn is_dir(&self) -> bool {
+        self.is(libc::S_IFDIR)
+    }
+
+    /// Returns true if this file type is a regular file.
+    ///
+    /// See also [`std::fs::FileType::is_file`].
+    pub fn is_file(&self) -> bool {
+        self.is(libc::S_IFREG)
+    }
+
+    /// Returns true if this file type is a symbolic link.
+    ///
+    /// See also [`std::fs::FileType::is_symlink`].
+    pub fn is_symlink(&self) -> bool {
+        self.is(libc::S_IFLNK)
+    }
+}
+
+impl FileType {
+    pub(super) fn from_mode(mode: libc::mode_t) -> Self {
+        Self(mode & libc::S_IFMT)
+    }
+
+    /// Returns the file type of a directory entry, if it is known.
+    pub(super) fn from_dirent(d_type: u8) -> Option<Self> {
+        let mode = match d_type {
+            libc::DT_FIFO => libc::S_IFIFO,
+            libc::DT_CHR => libc::S_IFCHR,
+            libc::DT_DIR => libc::S_IFDIR,
+            libc::DT_BLK => libc::S_IFBLK,
+            libc::DT_REG => libc::S_IFREG,
+            libc::DT_LNK => libc::S_IFLNK,
+            libc::DT_SOCK => libc::S_IFSOCK,
+            _ => return None,
+        };
+        Some(Self(mode))
+    }
+
+    fn is(&self, ty: libc::mode_t) -> bool {
+        self.0 == ty
+    }
+}
+
+impl fmt::Debug for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileType")
+            .field("is_dir", &self.is_dir())
+            .field("is_file", &self.is_file())
+            .field("is_symlink", &self.is_symlink())
+            .finish()
+    }
+}
+
+impl std::os::unix::fs::FileTypeExt for FileType {
+    fn is_block_device(&self) -> bool {
+        self.is(libc::S_IFBLK)
+    }
+
+    fn is_char_device(&self) -> bool {
+        self.is(libc::S_IFCHR)
+    }
+
+    fn is_fifo(&self) -> bool {
+        self.is(libc::S_IFIFO)
+    }
+
+    fn is_socket(&self) -> bool {
+        self.is(libc::S_IFSOCK)
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fmt,
+    future::{ready, Future},
+    io::{Error, ErrorKind, Result},
+    ops::{Deref, DerefMut},
+    os::fd::AsFd,
+    ptr::NonNull,
+    slice,
+};
+
+use super::{Advice, File};
+use crate::{io::ReadAt, runtime::syscall};
+
+/// A read-only memory map of a file.
+///
+/// The map dereferences to the contents of the file, so it can be read without
+/// submitting operations. Reads that fault in pages block the current thread,
+/// so [`Mmap::advise`] or [`Mmap::lock`] can be used to load the pages in
+/// advance.
+///
+/// See also `man mmap.2`.
+pub struct Mmap(Mapping);
+
+impl Mmap {
+    /// Maps the whole file into memory.
+    ///
+    /// The file must be opened for reading. The map has the size of the file
+    /// when it is created, and doesn't grow with the file.
+    ///
+    /// # Safety
+    ///
+    /// The map is shared with the file, so the caller must ensure that the
+    /// mapped range is not modified or truncated while the map is alive, by
+    /// this or another process. Otherwise, the contents of the slice may
+    /// change under it, and accessing truncated pages raises `SIGBUS`.
+    pub async unsafe fn map(file: &File) -> Result<Self> {
+        Mapping::new(file, libc::PROT_READ).await.map(Self)
+    }
+
+    /// Returns the length of this map in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    /// Returns true if this map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    /// Declares an access pattern for this map.
+    ///
+    /// [`Advice::NoReuse`] has no effect on maps.
+    ///
+    /// See also `man madvise.2`.
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.0.advise(advice)
+    }
+
+    /// Locks the pages of this map in memory.
+    ///
+    /// The pages are faulted in on the blocking pool.
+    ///
+    /// See also `man mlock.2`.
+    pub async fn lock(&self) -> Result<()> {
+        self.0.lock().await
+    }
+
+    /// Unlocks the pages of this map.
+    ///
+    /// See also `man munlock.2`.
+    pub fn unlock(&self) -> Result<()> {
+        self.0.unlock()
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl ReadAt for Mmap {
+    type ReadAt<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        ready(Ok(self.0.read_at(buf, pos)))
+    }
+}
+
+impl fmt::Debug for Mmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mmap").field("len", &self.0.len).finish()
+    }
+}
+
+/// A writable memory map of a file.
+///
+/// Writes to the map are written back to the file by the kernel, or explicitly
+/// with [`MmapMut::flush_async`].
+///
+/// See also [`Mmap`].
+pub struct MmapMut(Mapping);
+
+impl MmapMut {
+    /// Maps the whole file into memory.
+    ///
+    /// The file must be opened for reading and writing. The map has the size of
+    /// the file when it is created, and doesn't grow with the file.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the mapped range is not accessed, through
+    /// another map or otherwise, or truncated while the map is alive, by this
+    /// or another process. See also [`Mmap::map`].
+    pub async unsafe fn map_mut(file: &File) -> Result<Self> {
+        Mapping::new(file, libc::PROT_READ | libc::PROT_WRITE)
+            .await
+            .map(Self)
+    }
+
+    /// Returns the length of this map in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    /// Returns true if this map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    /// Writes modified pages of this map back to the file and waits for the
+    /// write-back to complete.
+    ///
+    /// io_uring doesn't support this operation, so it runs on the blocking
+    /// pool.
+    ///
+    /// See also `man msync.2`.
+    pub async fn flush_async(&self) -> Result<()> {
+        if self.0.len == 0 {
+            return Ok(());
+        }
+        syscall::msync(self.0.as_slice(), libc::MS_SYNC).await
+    }
+
+    /// Declares an access pattern for this map.
+    ///
+    /// See also [`Mmap::advise`].
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.0.advise(advice)
+    }
+
+    /// Locks the pages of this map in memory.
+    ///
+    /// See also [`Mmap::lock`].
+    pub async fn lock(&self) -> Result<()> {
+        self.0.lock().await
+    }
+
+    /// Unlocks the pages of this map.
+    ///
+    /// See also [`Mmap::unlock`].
+    pub fn unlock(&self) -> Result<()> {
+        self.0.unlock()
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl DerefMut for MmapMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.0.ptr.as_ptr(), self.0.len) }
+    }
+}
+
+impl AsRef<[u8]> for MmapMut {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for MmapMut {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl ReadAt for MmapMut {
+    type ReadAt<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        ready(Ok(self.0.read_at(buf, pos)))
+    }
+}
+
+impl fmt::Debug for MmapMut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapMut").field("len", &self.0.len).finish()
+    }
+}
+
+/// A shared mapping of a whole file.
+struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// Safety: the mapping is owned exclusively, and mutable access to its memory is
+// guarded by `MmapMut`.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    async fn new(file: &File, prot: libc::c_int) -> Result<Self> {
+        let len = file.metadata().await?.len();
+        let len = len
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "file is too large to map"))?;
+        let ptr = if len == 0 {
+            // Empty mappings are not allowed, so use a dangling pointer instead.
+            NonNull::dangling()
+        } else {
+            let ptr = syscall::mmap(file.as_fd(), len, prot, 0)?;
+            NonNull::new(ptr).unwrap()
+        };
+        Ok(Self { ptr, len })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    fn read_at(&self, buf: &mut [u8], pos: u64) -> usize {
+        let data = self.as_slice();
+        let start = pos.min(data.len() as u64) as usize;
+        let len = buf.len().min(data.len() - start);
+        buf[..len].copy_from_slice(&data[start..start + len]);
+        len
+    }
+
+    fn advise(&self, advice: Advice) -> Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+            Advice::NoReuse => return Ok(()),
+        };
+        if self.len == 0 {
+            return Ok(());
+        }
+        syscall::madvise(self.as_slice(), advice)
+    }
+
+    async fn lock(&self) -> Result<()> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        syscall::mlock(self.as_slice()).await
+    }
+
+    fn unlock(&self) -> Result<()> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        syscall::munlock(self.as_slice())
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            // This only fails if the arguments are invalid, and drop must not
+            // panic anyway.
+            let _ = unsafe { syscall::munmap(self.ptr.as_ptr(), self.len) };
+        }
+    }
+}
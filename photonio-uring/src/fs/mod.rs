@@ -2,7 +2,15 @@
 //!
 //! This module is an async version of [`std::fs`].
 
-use std::{io::Result, path::Path};
+use std::{
+    fs::Permissions,
+    io::{Error, ErrorKind, Result},
+    os::unix::{
+        fs::{MetadataExt, OpenOptionsExt, PermissionsExt},
+        io::AsFd,
+    },
+    path::{Path, PathBuf},
+};
 
 use crate::runtime::syscall;
 
@@ -10,16 +18,200 @@ mod open;
 pub use open::OpenOptions;
 
 mod file;
-pub use file::File;
+pub use file::{Advice, DirectAlignment, File, LockKind, SyncRangeFlags};
+
+mod mmap;
+pub use mmap::{Mmap, MmapMut};
 
 mod metadata;
-pub use metadata::Metadata;
+pub use metadata::{FileType, Metadata};
+
+mod dir;
+pub use dir::Dir;
+
+mod read_dir;
+pub use read_dir::{read_dir, DirEntry, ReadDir};
+
+mod watcher;
+pub use watcher::{Event, EventPath, WatchDescriptor, Watcher};
+
+mod xattr;
+pub use xattr::{get_xattr, list_xattr, remove_xattr, set_xattr};
+
+mod space;
+pub use space::{space, FsStats};
+
+mod temp;
+pub use temp::{tempfile, tempfile_in, NamedTempFile, TempDir};
+
+mod remove_dir_all;
+pub use remove_dir_all::remove_dir_all;
+
+mod atomic;
+pub use atomic::{write_atomic, AtomicFile};
+
+/// An async version of [`std::fs::metadata`].
+pub async fn metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    let path = path.as_ref();
+    syscall::stat(path, 0).await.map(Metadata::from)
+}
+
+/// An async version of [`std::fs::symlink_metadata`].
+pub async fn symlink_metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    let path = path.as_ref();
+    syscall::stat(path, libc::AT_SYMLINK_NOFOLLOW)
+        .await
+        .map(Metadata::from)
+}
+
+/// An async version of [`std::fs::read`].
+///
+/// The buffer is sized from the metadata of the file, so a regular file is
+/// usually read with a single operation.
+pub async fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    // These operations are not linked with `IOSQE_IO_LINK`. The statx needs
+    // the descriptor returned by the open, which a linked operation can only
+    // use with a registered file table, and this runtime doesn't register one.
+    // A statx by path would race with renames, and the read needs the size
+    // from the statx to allocate its buffer.
+    let fd = syscall::open(path, libc::O_RDONLY, 0).await?;
+    let metadata = Metadata::from(syscall::fstat(fd.as_fd()).await?);
+    let size = metadata.len() as usize;
+    // Reserve one more byte, so that a short read indicates the end of file.
+    let mut buf = vec![0; size + 1];
+    let mut len = 0;
+    loop {
+        if len == buf.len() {
+            buf.resize((len * 2).max(8 * 1024), 0);
+        }
+        match syscall::read(fd.as_fd(), &mut buf[len..]).await {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+        // A short read after the reported size is the end of a regular file.
+        // Pseudo files (e.g. in procfs) might report a size of zero, so they
+        // are always read until end of file.
+        if metadata.is_file() && size > 0 && len >= size && len < buf.len() {
+            break;
+        }
+    }
+    syscall::close(fd).await?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// An async version of [`std::fs::read_to_string`].
+pub async fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let buf = read(path).await?;
+    String::from_utf8(buf)
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8"))
+}
+
+/// An async version of [`std::fs::write`].
+pub async fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+    let flags = libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC;
+    let fd = syscall::open(path, flags, 0o666).await?;
+    let mut pos = 0;
+    while pos < contents.len() {
+        match syscall::write(fd.as_fd(), &contents[pos..]).await {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => pos += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    syscall::close(fd).await
+}
+
+/// An async version of [`std::fs::set_permissions`].
+pub async fn set_permissions<P: AsRef<Path>>(path: P, perm: Permissions) -> Result<()> {
+    let path = path.as_ref();
+    syscall::chmod(path, perm.mode()).await
+}
+
+/// Changes the owner and group of a file.
+///
+/// If `uid` or `gid` is `None`, the corresponding id is not changed. Symbolic
+/// links are followed.
+///
+/// See also [`std::os::unix::fs::chown`].
+pub async fn chown<P: AsRef<Path>>(path: P, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+    let path = path.as_ref();
+    syscall::chown(path, to_id(uid), to_id(gid), 0).await
+}
+
+/// This function is similar to [`chown`], except that it doesn't follow
+/// symbolic links.
+///
+/// See also [`std::os::unix::fs::lchown`].
+pub async fn lchown<P: AsRef<Path>>(path: P, uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+    let path = path.as_ref();
+    syscall::chown(path, to_id(uid), to_id(gid), libc::AT_SYMLINK_NOFOLLOW).await
+}
+
+/// An async version of [`std::fs::hard_link`].
+pub async fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<()> {
+    let original = original.as_ref();
+    let link = link.as_ref();
+    syscall::link(original, link, 0).await
+}
+
+/// Creates a new symbolic link on the filesystem.
+///
+/// See also [`std::os::unix::fs::symlink`].
+pub async fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<()> {
+    let original = original.as_ref();
+    let link = link.as_ref();
+    syscall::symlink(original, link).await
+}
+
+/// An async version of [`std::fs::read_link`].
+pub async fn read_link<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    syscall::readlink(path).await
+}
+
+/// An async version of [`std::fs::canonicalize`].
+pub async fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    syscall::realpath(path).await
+}
 
 /// An async version of [`std::fs::rename`].
 pub async fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     let from = from.as_ref();
     let to = to.as_ref();
-    syscall::rename(from, to).await
+    syscall::rename(from, to, 0).await
+}
+
+/// This function is similar to [`rename`], except that it fails with
+/// [`ErrorKind::AlreadyExists`] instead of replacing `to` if it exists.
+///
+/// The check and the rename are performed atomically. Not all filesystems
+/// support this operation.
+///
+/// See also `RENAME_NOREPLACE` in `man renameat2.2`.
+pub async fn rename_noreplace<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+    syscall::rename(from, to, libc::RENAME_NOREPLACE).await
+}
+
+/// Atomically exchanges the files at `a` and `b`.
+///
+/// Both files must exist. They can be of different types, e.g. one of them can
+/// be a directory. Not all filesystems support this operation.
+///
+/// See also `RENAME_EXCHANGE` in `man renameat2.2`.
+pub async fn exchange<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<()> {
+    let a = a.as_ref();
+    let b = b.as_ref();
+    syscall::rename(a, b, libc::RENAME_EXCHANGE).await
 }
 
 /// An async version of [`std::fs::remove_file`].
@@ -34,8 +226,117 @@ pub async fn create_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     syscall::mkdir(path, 0o777).await
 }
 
+/// An async version of [`std::fs::create_dir_all`].
+pub async fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    // Walk up until an existing ancestor is found, and then create the missing
+    // directories from there.
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if current.as_os_str().is_empty() {
+            break;
+        }
+        match syscall::mkdir(current, 0o777).await {
+            Ok(()) => break,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                missing.push(current);
+                current = current
+                    .parent()
+                    .ok_or_else(|| Error::new(ErrorKind::Other, "failed to create whole tree"))?;
+            }
+            Err(e) => {
+                if is_dir(current).await {
+                    break;
+                }
+                return Err(e);
+            }
+        }
+    }
+    for dir in missing.into_iter().rev() {
+        if let Err(e) = syscall::mkdir(dir, 0o777).await {
+            if !is_dir(dir).await {
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// An async version of [`std::fs::remove_dir`].
 pub async fn remove_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     let path = path.as_ref();
     syscall::rmdir(path).await
 }
+
+/// An async version of [`std::fs::copy`].
+///
+/// This function uses `copy_file_range` if possible, and falls back to reading
+/// and writing the data otherwise.
+pub async fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u64> {
+    let reader = File::open(from).await?;
+    let metadata = reader.metadata().await?;
+    if !metadata.is_file() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the source path is not an existing regular file",
+        ));
+    }
+    let mode = metadata.mode() & 0o7777;
+    let writer = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(to)
+        .await?;
+    syscall::fchmod(writer.as_fd(), mode).await?;
+
+    let mut written = 0;
+    loop {
+        match syscall::copy_file_range(reader.as_fd(), writer.as_fd(), 1 << 30).await {
+            // Some files (e.g. in procfs) report a size of zero and copy
+            // nothing, so they are read and written instead.
+            Ok(0) if written == 0 => break,
+            Ok(0) => return Ok(written),
+            Ok(n) => written += n as u64,
+            // The files are not supported by `copy_file_range`.
+            Err(e)
+                if written == 0
+                    && matches!(
+                        e.raw_os_error(),
+                        Some(libc::EXDEV | libc::EINVAL | libc::ENOSYS | libc::EOPNOTSUPP)
+                    ) =>
+            {
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = syscall::read(reader.as_fd(), &mut buf).await?;
+        if n == 0 {
+            return Ok(written);
+        }
+        let mut pos = 0;
+        while pos < n {
+            match syscall::write(writer.as_fd(), &buf[pos..n]).await? {
+                0 => return Err(ErrorKind::WriteZero.into()),
+                m => pos += m,
+            }
+        }
+        written += n as u64;
+    }
+}
+
+/// Converts an optional id to the argument of `chown`.
+fn to_id(id: Option<u32>) -> u32 {
+    // `-1` means that the id should not be changed.
+    id.unwrap_or(u32::MAX)
+}
+
+async fn is_dir(path: &Path) -> bool {
+    metadata(path).await.map_or(false, |m| m.is_dir())
+}
@@ -1,4 +1,8 @@
-use std::{io::Result, os::unix::fs::OpenOptionsExt, path::Path};
+use std::{
+    io::Result,
+    os::unix::{fs::OpenOptionsExt, io::BorrowedFd},
+    path::Path,
+};
 
 use super::File;
 use crate::runtime::syscall;
@@ -14,6 +18,7 @@ pub struct OpenOptions {
     truncate: bool,
     create: bool,
     create_new: bool,
+    direct: bool,
     mode: u32,
     custom_flags: i32,
 }
@@ -28,6 +33,7 @@ impl OpenOptions {
             truncate: false,
             create: false,
             create_new: false,
+            direct: false,
             mode: 0o666,
             custom_flags: 0,
         }
@@ -69,16 +75,36 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the option to bypass the page cache with direct I/O.
+    ///
+    /// Reads and writes on the opened file must use buffers, offsets, and
+    /// lengths aligned to [`File::direct_alignment`]. Misaligned operations
+    /// fail with [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput)
+    /// before they are submitted.
+    ///
+    /// See also `O_DIRECT` in `man open.2`.
+    pub fn direct(&mut self, direct: bool) -> &mut Self {
+        self.direct = direct;
+        self
+    }
+
     /// See also [`std::fs::OpenOptions::open`].
     pub async fn open<P: AsRef<Path>>(&self, path: P) -> Result<File> {
         let path = path.as_ref();
-        syscall::open(path, self.flags(), self.mode)
-            .await
-            .map(File::from)
+        let flags = self.flags();
+        let fd = syscall::open(path, flags, self.mode).await?;
+        File::with_flags(fd, flags).await
     }
 }
 
 impl OpenOptions {
+    /// Opens a file at `path` relative to the directory `dir`.
+    pub(super) async fn open_at(&self, dir: BorrowedFd<'_>, path: &Path) -> Result<File> {
+        let flags = self.flags();
+        let fd = syscall::openat(dir, path, flags, self.mode).await?;
+        File::with_flags(fd, flags).await
+    }
+
     fn flags(&self) -> libc::c_int {
         let mut flags = match (self.read, self.write, self.append) {
             (true, _, true) => libc::O_RDWR | libc::O_APPEND,
@@ -98,6 +124,9 @@ impl OpenOptions {
                 flags |= libc::O_TRUNC;
             }
         }
+        if self.direct {
+            flags |= libc::O_DIRECT;
+        }
         flags |= self.custom_flags & !libc::O_ACCMODE;
         flags
     }
@@ -0,0 +1,226 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    future::poll_fn,
+    io::Result,
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsFd, BorrowedFd, OwnedFd},
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use futures::{FutureExt, Stream};
+
+#[cfg(doc)]
+use super::Dir;
+use super::{FileType, Metadata};
+use crate::runtime::{syscall, Blocking};
+
+/// Returns a stream over the entries within a directory.
+///
+/// The entries `.` and `..` are skipped.
+///
+/// See also [`std::fs::read_dir`].
+pub async fn read_dir<P: AsRef<Path>>(path: P) -> Result<ReadDir> {
+    let path = path.as_ref();
+    let flags = libc::O_RDONLY | libc::O_DIRECTORY;
+    let dir = syscall::open(path, flags, 0).await?;
+    Ok(ReadDir::new(dir, Some(path)))
+}
+
+/// A stream over the entries within a directory.
+///
+/// This stream is returned by [`read_dir`]. The entries are read in batches on
+/// the blocking pool, since io_uring can't read directories.
+///
+/// See also [`std::fs::ReadDir`].
+pub struct ReadDir {
+    dir: Arc<OwnedFd>,
+    root: Option<Arc<Path>>,
+    buf: Vec<u8>,
+    pos: usize,
+    pending: Option<Blocking<(Vec<u8>, Result<usize>)>>,
+    is_eof: bool,
+}
+
+impl ReadDir {
+    const BUF_SIZE: usize = 8 * 1024;
+
+    /// Returns the next entry in the directory, or `None` if there are no more
+    /// entries.
+    pub async fn next_entry(&mut self) -> Result<Option<DirEntry>> {
+        poll_fn(|cx| self.poll_next_entry(cx)).await
+    }
+
+    /// Polls for the next entry in the directory.
+    pub fn poll_next_entry(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<DirEntry>>> {
+        loop {
+            while self.pos < self.buf.len() {
+                if let Some(entry) = self.next_buffered_entry() {
+                    return Poll::Ready(Ok(Some(entry)));
+                }
+            }
+            if self.is_eof {
+                return Poll::Ready(Ok(None));
+            }
+            let pending = self.pending.get_or_insert_with(|| {
+                let mut buf = std::mem::take(&mut self.buf);
+                buf.resize(Self::BUF_SIZE, 0);
+                syscall::getdents(self.dir.clone(), buf)
+            });
+            let (mut buf, res) = ready!(pending.poll_unpin(cx));
+            self.pending = None;
+            let len = match res {
+                Ok(len) => len,
+                Err(err) => {
+                    buf.clear();
+                    self.buf = buf;
+                    return Poll::Ready(Err(err));
+                }
+            };
+            buf.truncate(len);
+            self.buf = buf;
+            self.pos = 0;
+            self.is_eof = len == 0;
+        }
+    }
+}
+
+impl ReadDir {
+    /// Creates a stream over the entries within `dir`, which is the directory
+    /// at `root`.
+    ///
+    /// If `root` is `None`, the paths of the entries are relative to `dir`.
+    pub(super) fn new(dir: OwnedFd, root: Option<&Path>) -> Self {
+        Self {
+            dir: Arc::new(dir),
+            root: root.map(Arc::from),
+            buf: Vec::with_capacity(Self::BUF_SIZE),
+            pos: 0,
+            pending: None,
+            is_eof: false,
+        }
+    }
+
+    pub(super) fn dir_fd(&self) -> BorrowedFd<'_> {
+        self.dir.as_fd()
+    }
+
+    /// Parses the `linux_dirent64` at the current position.
+    ///
+    /// Returns `None` if the entry is `.` or `..`.
+    fn next_buffered_entry(&mut self) -> Option<DirEntry> {
+        // See `man getdents64.2` for the layout.
+        const RECLEN_OFFSET: usize = 16;
+        const TYPE_OFFSET: usize = 18;
+        const NAME_OFFSET: usize = 19;
+
+        let buf = &self.buf[self.pos..];
+        let ino = u64::from_ne_bytes(buf[..8].try_into().unwrap());
+        let reclen = u16::from_ne_bytes(buf[RECLEN_OFFSET..TYPE_OFFSET].try_into().unwrap());
+        let reclen = reclen as usize;
+        let d_type = buf[TYPE_OFFSET];
+        let name = &buf[NAME_OFFSET..reclen];
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap()];
+        self.pos += reclen;
+        if name == b"." || name == b".." {
+            return None;
+        }
+        Some(DirEntry {
+            dir: self.dir.clone(),
+            root: self.root.clone(),
+            name: OsStr::from_bytes(name).to_owned(),
+            ino,
+            file_type: FileType::from_dirent(d_type),
+        })
+    }
+}
+
+impl Stream for ReadDir {
+    type Item = Result<DirEntry>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_entry(cx).map(Result::transpose)
+    }
+}
+
+impl fmt::Debug for ReadDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadDir").field(&self.root).finish()
+    }
+}
+
+/// An entry returned by [`ReadDir`].
+///
+/// See also [`std::fs::DirEntry`].
+pub struct DirEntry {
+    dir: Arc<OwnedFd>,
+    root: Option<Arc<Path>>,
+    name: OsString,
+    ino: u64,
+    file_type: Option<FileType>,
+}
+
+impl DirEntry {
+    /// Returns the full path to the file that this entry represents.
+    ///
+    /// For entries returned by [`Dir::read_dir`], the path is relative to that
+    /// directory, which is where the methods of [`Dir`] resolve it.
+    ///
+    /// See also [`std::fs::DirEntry::path`].
+    pub fn path(&self) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(&self.name),
+            None => PathBuf::from(&self.name),
+        }
+    }
+
+    /// Returns the file name of this entry.
+    ///
+    /// See also [`std::fs::DirEntry::file_name`].
+    pub fn file_name(&self) -> OsString {
+        self.name.clone()
+    }
+
+    /// Returns the inode number of this entry.
+    ///
+    /// See also [`std::os::unix::fs::DirEntryExt::ino`].
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// Returns the metadata for the file that this entry represents.
+    ///
+    /// This function doesn't traverse symbolic links.
+    ///
+    /// See also [`std::fs::DirEntry::metadata`].
+    pub async fn metadata(&self) -> Result<Metadata> {
+        let path = Path::new(&self.name);
+        syscall::statx(self.dir.as_fd(), path, libc::AT_SYMLINK_NOFOLLOW)
+            .await
+            .map(Metadata::from)
+    }
+
+    /// Returns the file type for the file that this entry represents.
+    ///
+    /// This function doesn't traverse symbolic links. It only queries the
+    /// filesystem if the type is not returned with the entry.
+    ///
+    /// See also [`std::fs::DirEntry::file_type`].
+    pub async fn file_type(&self) -> Result<FileType> {
+        match self.file_type {
+            Some(file_type) => Ok(file_type),
+            None => self.metadata().await.map(|m| m.file_type()),
+        }
+    }
+}
+
+impl fmt::Debug for DirEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DirEntry").field(&self.path()).finish()
+    }
+}
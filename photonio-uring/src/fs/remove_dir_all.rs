@@ -0,0 +1,71 @@
+use std::{
+    io::{ErrorKind, Result},
+    os::unix::io::OwnedFd,
+    path::Path,
+};
+
+use futures::{future::BoxFuture, FutureExt};
+
+use super::{symlink_metadata, ReadDir};
+use crate::runtime::syscall;
+
+/// An async version of [`std::fs::remove_dir_all`].
+///
+/// This function doesn't follow symbolic links. Entries are opened and removed
+/// relative to the file descriptor of their parent directory, so a directory
+/// that is replaced with a symbolic link during the removal is not followed
+/// either.
+pub async fn remove_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if symlink_metadata(path).await?.is_symlink() {
+        return syscall::unlink(path).await;
+    }
+    let dir = syscall::open(path, OPEN_DIR_FLAGS, 0).await?;
+    remove_dir_contents(dir, path).await?;
+    syscall::rmdir(path).await
+}
+
+const OPEN_DIR_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW;
+
+fn remove_dir_contents(dir: OwnedFd, path: &Path) -> BoxFuture<'_, Result<()>> {
+    async move {
+        let mut entries = ReadDir::new(dir, Some(path));
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let name = Path::new(&name);
+            let dir = entries.dir_fd();
+            let is_dir = match entry.file_type().await {
+                Ok(file_type) => file_type.is_dir(),
+                // The entry has been removed by someone else.
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            if !is_dir {
+                ignore_not_found(syscall::unlinkat(dir, name, 0).await)?;
+                continue;
+            }
+            match syscall::openat(dir, name, OPEN_DIR_FLAGS, 0).await {
+                Ok(child) => {
+                    remove_dir_contents(child, &entry.path()).await?;
+                    let res = syscall::unlinkat(dir, name, libc::AT_REMOVEDIR).await;
+                    ignore_not_found(res)?;
+                }
+                // The directory has been replaced with something else.
+                Err(e) if matches!(e.raw_os_error(), Some(libc::ENOTDIR | libc::ELOOP)) => {
+                    ignore_not_found(syscall::unlinkat(dir, name, 0).await)?;
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+    .boxed()
+}
+
+fn ignore_not_found(res: Result<()>) -> Result<()> {
+    match res {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
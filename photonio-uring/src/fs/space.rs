@@ -0,0 +1,95 @@
+use std::{fmt, io::Result, os::unix::io::AsFd, path::Path};
+
+use super::File;
+use crate::runtime::syscall;
+
+/// Returns the space usage of the filesystem that contains `path`.
+///
+/// io_uring doesn't support this operation, so it runs on the blocking pool.
+///
+/// See also `man statfs.2`.
+pub async fn space<P: AsRef<Path>>(path: P) -> Result<FsStats> {
+    let path = path.as_ref();
+    syscall::statfs(path).await.map(FsStats)
+}
+
+impl File {
+    /// Returns the space usage of the filesystem that contains this file.
+    ///
+    /// See also [`space`].
+    pub async fn space(&self) -> Result<FsStats> {
+        syscall::fstatfs(self.as_fd()).await.map(FsStats)
+    }
+}
+
+/// The space usage of a filesystem, returned by [`space`] and [`File::space`].
+#[derive(Clone)]
+pub struct FsStats(libc::statfs64);
+
+impl FsStats {
+    /// Returns the size of the filesystem in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.0.f_blocks * self.fragment_size()
+    }
+
+    /// Returns the number of free bytes in the filesystem.
+    pub fn free_bytes(&self) -> u64 {
+        self.0.f_bfree * self.fragment_size()
+    }
+
+    /// Returns the number of free bytes available to unprivileged users.
+    ///
+    /// This is less than [`FsStats::free_bytes`] if some blocks are reserved
+    /// for privileged users.
+    pub fn available_bytes(&self) -> u64 {
+        self.0.f_bavail * self.fragment_size()
+    }
+
+    /// Returns the total number of inodes in the filesystem.
+    ///
+    /// Some filesystems allocate inodes dynamically and report zero here.
+    pub fn total_inodes(&self) -> u64 {
+        self.0.f_files
+    }
+
+    /// Returns the number of free inodes in the filesystem.
+    pub fn free_inodes(&self) -> u64 {
+        self.0.f_ffree
+    }
+
+    /// Returns the optimal transfer block size of the filesystem.
+    pub fn block_size(&self) -> u64 {
+        self.0.f_bsize as u64
+    }
+
+    /// Returns the magic number of the filesystem type, e.g. `0xEF53` for
+    /// ext4.
+    ///
+    /// See also `man statfs.2` for the known values.
+    pub fn fs_type(&self) -> u64 {
+        self.0.f_type as u64
+    }
+
+    // The block counts are in units of the fragment size, which older kernels
+    // don't report.
+    fn fragment_size(&self) -> u64 {
+        match self.0.f_frsize {
+            0 => self.0.f_bsize as u64,
+            size => size as u64,
+        }
+    }
+}
+
+impl fmt::Debug for FsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsStats")
+            .field("total_bytes", &self.total_bytes())
+            .field("free_bytes", &self.free_bytes())
+            .field("available_bytes", &self.available_bytes())
+            .field("total_inodes", &self.total_inodes())
+            .field("free_inodes", &self.free_inodes())
+            .field("block_size", &self.block_size())
+            .field("fs_type", &format_args!("{:#x}", self.fs_type()))
+            .finish()
+    }
+}
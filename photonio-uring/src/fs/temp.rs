@@ -0,0 +1,213 @@
+use std::{
+    io::{ErrorKind, Result},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+use photonio_base::fs::temp_name;
+
+use super::{remove_dir_all, File, OpenOptions};
+use crate::{
+    io::{Read, ReadAt, Write, WriteAt},
+    runtime::syscall,
+};
+
+/// Creates an anonymous temporary file in [`std::env::temp_dir`].
+///
+/// See also [`tempfile_in`].
+pub async fn tempfile() -> Result<File> {
+    tempfile_in(std::env::temp_dir()).await
+}
+
+/// Creates an anonymous temporary file in `dir`.
+///
+/// The file is opened for reading and writing, and is removed when it is
+/// closed. If the filesystem supports `O_TMPFILE`, the file never has a name.
+/// Otherwise, a named file is created and removed right away.
+pub async fn tempfile_in<P: AsRef<Path>>(dir: P) -> Result<File> {
+    let dir = dir.as_ref();
+    let res = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .mode(0o600)
+        .custom_flags(libc::O_TMPFILE)
+        .open(dir)
+        .await;
+    match res {
+        Ok(file) => return Ok(file),
+        // The filesystem doesn't support `O_TMPFILE`.
+        Err(e) if matches!(e.raw_os_error(), Some(libc::EOPNOTSUPP | libc::EISDIR)) => {}
+        Err(e) => return Err(e),
+    }
+    let (file, path) = create_named(dir).await?;
+    syscall::unlink(&path).await?;
+    Ok(file)
+}
+
+/// A named temporary file that is removed when dropped.
+///
+/// Unlike [`tempfile`], the file can be opened again by its path, and can be
+/// kept with [`NamedTempFile::persist`].
+#[derive(Debug)]
+pub struct NamedTempFile {
+    file: File,
+    path: TempPath,
+}
+
+impl NamedTempFile {
+    /// Creates a named temporary file in [`std::env::temp_dir`].
+    pub async fn new() -> Result<Self> {
+        Self::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates a named temporary file in `dir`.
+    ///
+    /// The file is opened for reading and writing.
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let (file, path) = create_named(dir.as_ref()).await?;
+        Ok(Self {
+            file,
+            path: TempPath(Some(path)),
+        })
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        self.path.0.as_deref().unwrap()
+    }
+
+    /// Returns the file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Moves the file to `path`, replacing any file that exists there, and
+    /// returns it instead of removing it.
+    ///
+    /// The file is moved with a rename, so `path` must be on the same
+    /// filesystem. If the rename fails, the file is removed.
+    pub async fn persist<P: AsRef<Path>>(self, path: P) -> Result<File> {
+        let Self {
+            file,
+            path: mut guard,
+        } = self;
+        let temp = guard.0.take().unwrap();
+        if let Err(e) = syscall::rename(&temp, path.as_ref(), 0).await {
+            let _ = syscall::unlink(&temp).await;
+            return Err(e);
+        }
+        Ok(file)
+    }
+}
+
+/// The path of a [`NamedTempFile`], which is removed when dropped.
+#[derive(Debug)]
+struct TempPath(Option<PathBuf>);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Read for NamedTempFile {
+    type Read<'a> = <File as Read>::Read<'a>;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        self.file.read(buf)
+    }
+}
+
+impl ReadAt for NamedTempFile {
+    type ReadAt<'a> = <File as ReadAt>::ReadAt<'a>;
+
+    fn read_at<'a>(&'a self, buf: &'a mut [u8], pos: u64) -> Self::ReadAt<'a> {
+        self.file.read_at(buf, pos)
+    }
+}
+
+impl Write for NamedTempFile {
+    type Write<'a> = <File as Write>::Write<'a>;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        self.file.write(buf)
+    }
+}
+
+impl WriteAt for NamedTempFile {
+    type WriteAt<'a> = <File as WriteAt>::WriteAt<'a>;
+
+    fn write_at<'a>(&'a self, buf: &'a [u8], pos: u64) -> Self::WriteAt<'a> {
+        self.file.write_at(buf, pos)
+    }
+}
+
+/// A temporary directory that is removed with its contents when dropped.
+///
+/// Dropping it removes the directory recursively on the current thread, which
+/// blocks the worker for as long as that takes. [`TempDir::close`] must be
+/// awaited to remove the directory without blocking.
+#[derive(Debug)]
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    /// Creates a temporary directory in [`std::env::temp_dir`].
+    pub async fn new() -> Result<Self> {
+        Self::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates a temporary directory in `dir`.
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        loop {
+            let path = dir.join(temp_name(".tmp", ""));
+            match syscall::mkdir(&path, 0o700).await {
+                Ok(()) => return Ok(Self { path: Some(path) }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap()
+    }
+
+    /// Removes the directory and its contents.
+    pub async fn close(mut self) -> Result<()> {
+        let path = self.path.take().unwrap();
+        remove_dir_all(path).await
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = std::fs::remove_dir_all(path);
+        }
+    }
+}
+
+/// Creates a file with a unique name in `dir`.
+async fn create_named(dir: &Path) -> Result<(File, PathBuf)> {
+    loop {
+        let path = dir.join(temp_name(".tmp", ""));
+        let res = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .await;
+        match res {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    future::{poll_fn, Future},
+    io::{ErrorKind, Result},
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+    },
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use futures::Stream;
+
+use crate::runtime::syscall;
+
+type PollFuture = Pin<Box<dyn Future<Output = Result<libc::c_short>> + Send>>;
+
+/// A pending poll for events.
+struct PendingPoll(PollFuture);
+
+// Safety: the future is only accessed through `&mut Watcher`, so it is never
+// shared between threads.
+unsafe impl Sync for PendingPoll {}
+
+/// Watches files and directories for changes.
+///
+/// The instance is polled for events with io_uring, and the events are
+/// returned by [`Watcher::next_event`] or as a [`Stream`]. A pending poll is
+/// cancelled when this is dropped.
+///
+/// See also `man inotify.7`.
+pub struct Watcher {
+    fd: Arc<OwnedFd>,
+    buf: Vec<u8>,
+    pos: usize,
+    pending: Option<PendingPoll>,
+}
+
+impl Watcher {
+    // Large enough for at least one event with the longest name.
+    const BUF_SIZE: usize = 4096;
+
+    // The events that are reported for a watch.
+    const MASK: u32 = libc::IN_CREATE
+        | libc::IN_MODIFY
+        | libc::IN_DELETE
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    /// Creates a watcher without any watch.
+    pub fn new() -> Result<Self> {
+        let fd = syscall::inotify_init()?;
+        Ok(Self {
+            fd: Arc::new(fd),
+            buf: Vec::with_capacity(Self::BUF_SIZE),
+            pos: 0,
+            pending: None,
+        })
+    }
+
+    /// Watches the file or directory at `path`.
+    ///
+    /// Events for a directory are reported for the directory itself and the
+    /// entries within it, but not for deeper entries. Watching a path that is
+    /// already watched returns the same descriptor.
+    pub async fn add<P: AsRef<Path>>(&self, path: P) -> Result<WatchDescriptor> {
+        let path = path.as_ref();
+        syscall::inotify_add_watch(self.fd.as_fd(), path, Self::MASK)
+            .await
+            .map(WatchDescriptor)
+    }
+
+    /// Removes a watch.
+    ///
+    /// An [`Event::Removed`] event is reported for the watch afterwards.
+    pub fn remove(&self, wd: WatchDescriptor) -> Result<()> {
+        syscall::inotify_rm_watch(self.fd.as_fd(), wd.0)
+    }
+
+    /// Returns the next event, waiting until one is available.
+    pub async fn next_event(&mut self) -> Result<Event> {
+        poll_fn(|cx| self.poll_next_event(cx)).await
+    }
+
+    /// Polls for the next event.
+    pub fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Result<Event>> {
+        loop {
+            if let Some(event) = self.next_buffered_event() {
+                return Poll::Ready(Ok(event));
+            }
+            if let Some(pending) = &mut self.pending {
+                let res = ready!(pending.0.as_mut().poll(cx));
+                self.pending = None;
+                res?;
+            }
+            // The instance is read in non-blocking mode after a poll, instead of
+            // with a read operation. A read would lend the buffer to the kernel
+            // until it completes, while callers may stop waiting for an event
+            // at any time (e.g. in `select!`), which would free the buffer or
+            // panic. A poll doesn't point to any memory, so it is cancelled when
+            // dropped. Events that are already queued are read right away, so
+            // the extra poll is only paid when the watcher has to wait anyway.
+            self.buf.resize(Self::BUF_SIZE, 0);
+            match syscall::try_read(self.fd.as_fd(), &mut self.buf) {
+                Ok(len) => {
+                    self.buf.truncate(len);
+                    self.pos = 0;
+                }
+                Err(err) => {
+                    self.buf.clear();
+                    if err.kind() != ErrorKind::WouldBlock {
+                        return Poll::Ready(Err(err));
+                    }
+                    // Wait until the instance is readable, and then read again.
+                    let fd = self.fd.clone();
+                    self.pending = Some(PendingPoll(Box::pin(async move {
+                        syscall::poll(fd.as_fd(), libc::POLLIN).await
+                    })));
+                }
+            }
+        }
+    }
+}
+
+impl Watcher {
+    /// Returns the next event in the buffer, pairing the halves of moves that
+    /// are read together.
+    fn next_buffered_event(&mut self) -> Option<Event> {
+        while self.pos < self.buf.len() {
+            let (raw, len) = RawEvent::parse(&self.buf[self.pos..]);
+            self.pos += len;
+            if raw.mask & libc::IN_MOVED_FROM != 0 && self.pos < self.buf.len() {
+                let (next, next_len) = RawEvent::parse(&self.buf[self.pos..]);
+                if next.mask & libc::IN_MOVED_TO != 0 && next.cookie == raw.cookie {
+                    self.pos += next_len;
+                    return Some(Event::Move {
+                        from: raw.into_path(),
+                        to: next.into_path(),
+                    });
+                }
+            }
+            if let Some(event) = raw.into_event() {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+impl Stream for Watcher {
+    type Item = Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_event(cx).map(Some)
+    }
+}
+
+impl fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Watcher").field(&self.fd).finish()
+    }
+}
+
+#[doc(hidden)]
+impl AsFd for Watcher {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for Watcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// A watch returned by [`Watcher::add`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchDescriptor(libc::c_int);
+
+/// The path of an event, relative to a watch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventPath {
+    wd: WatchDescriptor,
+    name: Option<OsString>,
+}
+
+impl EventPath {
+    /// Returns the watch that reported the event.
+    pub fn watch(&self) -> WatchDescriptor {
+        self.wd
+    }
+
+    /// Returns the name of the entry within the watched directory, or `None`
+    /// if the event is for the watched file or directory itself.
+    pub fn name(&self) -> Option<&OsStr> {
+        self.name.as_deref()
+    }
+}
+
+/// An event returned by [`Watcher`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A file or directory was created.
+    Create(EventPath),
+    /// A file was modified.
+    Modify(EventPath),
+    /// A file or directory was deleted.
+    Delete(EventPath),
+    /// A file or directory was moved between watched directories.
+    Move {
+        /// The path before the move.
+        from: EventPath,
+        /// The path after the move.
+        to: EventPath,
+    },
+    /// A file or directory was moved out of a watched directory.
+    ///
+    /// If the other half of a move is not read together with this one, it is
+    /// reported as a separate [`Event::MovedTo`] with the same cookie.
+    MovedFrom {
+        /// The path before the move.
+        path: EventPath,
+        /// The cookie that identifies the move.
+        cookie: u32,
+    },
+    /// A file or directory was moved into a watched directory.
+    MovedTo {
+        /// The path after the move.
+        path: EventPath,
+        /// The cookie that identifies the move.
+        cookie: u32,
+    },
+    /// A watch was removed, either explicitly or because the watched file or
+    /// directory was deleted.
+    Removed(WatchDescriptor),
+    /// Some events were lost because the event queue overflowed.
+    Overflow,
+}
+
+/// An `inotify_event` as returned by the kernel.
+struct RawEvent {
+    wd: libc::c_int,
+    mask: u32,
+    cookie: u32,
+    name: Option<OsString>,
+}
+
+impl RawEvent {
+    /// Parses the event at the start of `buf`.
+    ///
+    /// Returns the event and its length in the buffer.
+    fn parse(buf: &[u8]) -> (Self, usize) {
+        // See `man inotify.7` for the layout.
+        const HEADER_LEN: usize = 16;
+
+        let field = |i: usize| u32::from_ne_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        let len = field(3) as usize;
+        let name = &buf[HEADER_LEN..HEADER_LEN + len];
+        // The name is padded with NUL bytes.
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(len)];
+        let event = Self {
+            wd: field(0) as libc::c_int,
+            mask: field(1),
+            cookie: field(2),
+            name: (!name.is_empty()).then(|| OsStr::from_bytes(name).to_owned()),
+        };
+        (event, HEADER_LEN + len)
+    }
+
+    fn into_path(self) -> EventPath {
+        EventPath {
+            wd: WatchDescriptor(self.wd),
+            name: self.name,
+        }
+    }
+
+    fn into_event(self) -> Option<Event> {
+        let mask = self.mask;
+        let cookie = self.cookie;
+        let event = if mask & libc::IN_Q_OVERFLOW != 0 {
+            Event::Overflow
+        } else if mask & libc::IN_IGNORED != 0 {
+            Event::Removed(WatchDescriptor(self.wd))
+        } else if mask & libc::IN_CREATE != 0 {
+            Event::Create(self.into_path())
+        } else if mask & libc::IN_MODIFY != 0 {
+            Event::Modify(self.into_path())
+        } else if mask & (libc::IN_DELETE | libc::IN_DELETE_SELF) != 0 {
+            Event::Delete(self.into_path())
+        } else if mask & libc::IN_MOVED_FROM != 0 {
+            Event::MovedFrom {
+                path: self.into_path(),
+                cookie,
+            }
+        } else if mask & libc::IN_MOVED_TO != 0 {
+            Event::MovedTo {
+                path: self.into_path(),
+                cookie,
+            }
+        } else {
+            return None;
+        };
+        Some(event)
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io::Result,
+    os::unix::{ffi::OsStrExt, io::AsFd},
+    path::Path,
+};
+
+use super::File;
+use crate::runtime::syscall::{self, XattrTarget};
+
+/// Returns the value of an extended attribute of a file, or `None` if the
+/// attribute doesn't exist.
+///
+/// This function traverses symbolic links.
+///
+/// See also `man getxattr.2`.
+pub async fn get_xattr<P, N>(path: P, name: N) -> Result<Option<Vec<u8>>>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    get(XattrTarget::Path(path.as_ref()), name.as_ref()).await
+}
+
+/// Sets the value of an extended attribute of a file.
+///
+/// This function traverses symbolic links.
+///
+/// See also `man setxattr.2`.
+pub async fn set_xattr<P, N>(path: P, name: N, value: &[u8]) -> Result<()>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    syscall::setxattr(XattrTarget::Path(path.as_ref()), name.as_ref(), value, 0).await
+}
+
+/// Removes an extended attribute of a file.
+///
+/// This function traverses symbolic links.
+///
+/// See also `man removexattr.2`.
+pub async fn remove_xattr<P, N>(path: P, name: N) -> Result<()>
+where
+    P: AsRef<Path>,
+    N: AsRef<OsStr>,
+{
+    syscall::removexattr(XattrTarget::Path(path.as_ref()), name.as_ref()).await
+}
+
+/// Returns the names of the extended attributes of a file.
+///
+/// This function traverses symbolic links.
+///
+/// See also `man listxattr.2`.
+pub async fn list_xattr<P: AsRef<Path>>(path: P) -> Result<Vec<OsString>> {
+    list(XattrTarget::Path(path.as_ref())).await
+}
+
+impl File {
+    /// Returns the value of an extended attribute of this file, or `None` if
+    /// the attribute doesn't exist.
+    ///
+    /// See also `man fgetxattr.2`.
+    pub async fn get_xattr<N: AsRef<OsStr>>(&self, name: N) -> Result<Option<Vec<u8>>> {
+        get(XattrTarget::Fd(self.as_fd()), name.as_ref()).await
+    }
+
+    /// Sets the value of an extended attribute of this file.
+    ///
+    /// See also `man fsetxattr.2`.
+    pub async fn set_xattr<N: AsRef<OsStr>>(&self, name: N, value: &[u8]) -> Result<()> {
+        syscall::setxattr(XattrTarget::Fd(self.as_fd()), name.as_ref(), value, 0).await
+    }
+
+    /// Removes an extended attribute of this file.
+    ///
+    /// See also `man fremovexattr.2`.
+    pub async fn remove_xattr<N: AsRef<OsStr>>(&self, name: N) -> Result<()> {
+        syscall::removexattr(XattrTarget::Fd(self.as_fd()), name.as_ref()).await
+    }
+
+    /// Returns the names of the extended attributes of this file.
+    ///
+    /// See also `man flistxattr.2`.
+    pub async fn list_xattr(&self) -> Result<Vec<OsString>> {
+        list(XattrTarget::Fd(self.as_fd())).await
+    }
+}
+
+// The initial buffer size, which fits most values without querying the size.
+const BUF_SIZE: usize = 256;
+
+async fn get(target: XattrTarget<'_>, name: &OsStr) -> Result<Option<Vec<u8>>> {
+    let mut buf = vec![0; BUF_SIZE];
+    loop {
+        match syscall::getxattr(target, name, &mut buf).await {
+            Ok(len) => {
+                buf.truncate(len);
+                return Ok(Some(buf));
+            }
+            Err(err) if err.raw_os_error() == Some(libc::ENODATA) => return Ok(None),
+            // The value doesn't fit in the buffer. Query its size and retry,
+            // since it may grow again in between.
+            Err(err) if err.raw_os_error() == Some(libc::ERANGE) => {
+                let len = syscall::getxattr(target, name, &mut []).await?;
+                buf.resize(len.max(buf.len() * 2), 0);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+async fn list(target: XattrTarget<'_>) -> Result<Vec<OsString>> {
+    let mut buf = vec![0; BUF_SIZE];
+    let len = loop {
+        match syscall::listxattr(target, &mut buf).await {
+            Ok(len) => break len,
+            Err(err) if err.raw_os_error() == Some(libc::ERANGE) => {
+                let len = syscall::listxattr(target, &mut []).await?;
+                buf.resize(len.max(buf.len() * 2), 0);
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    // The names are separated by NUL bytes.
+    let names = buf[..len]
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| OsStr::from_bytes(name).to_owned())
+        .collect();
+    Ok(names)
+}
@@ -114,6 +114,13 @@ impl TcpStream {
         Ok(stream)
     }
 
+    /// Creates a new handle to the same socket.
+    ///
+    /// See also [`std::net::TcpStream::try_clone`].
+    pub fn try_clone(&self) -> Result<Self> {
+        self.0.try_clone().map(Self)
+    }
+
     /// Shuts down the read, write, or both halves of this connection.
     ///
     /// See also [`std::net::TcpStream::shutdown`].
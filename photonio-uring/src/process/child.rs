@@ -0,0 +1,303 @@
+use std::{
+    fmt,
+    future::Future,
+    io::{ErrorKind, Result},
+    os::unix::{
+        io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        process::ExitStatusExt,
+    },
+    process,
+};
+
+use futures::FutureExt;
+
+use super::{ExitStatus, Output};
+use crate::{
+    io::{Read, Write},
+    runtime::{syscall, Blocking},
+};
+
+/// A spawned child process.
+///
+/// This type is an async version of [`std::process::Child`]. The child is
+/// reaped through a pidfd, which is waited for with io_uring. If the kernel
+/// doesn't support pidfds (before Linux 5.3), the child is waited for with a
+/// blocking `waitpid` on the blocking pool instead.
+///
+/// Like [`std::process::Child`], dropping this neither kills nor reaps the
+/// child.
+pub struct Child {
+    pid: u32,
+    /// The pidfd of the child, or `None` if the kernel doesn't support pidfds.
+    pidfd: Option<OwnedFd>,
+    /// A pending `waitpid` on the blocking pool, kept if a wait is cancelled
+    /// so that the status is not lost.
+    waiting: Option<Blocking<Result<libc::c_int>>>,
+    status: Option<ExitStatus>,
+    /// The standard input of the child, if it is piped.
+    pub stdin: Option<ChildStdin>,
+    /// The standard output of the child, if it is piped.
+    pub stdout: Option<ChildStdout>,
+    /// The standard error of the child, if it is piped.
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    pub(super) fn new(mut child: process::Child) -> Result<Self> {
+        let pid = child.id();
+        let pidfd = match syscall::pidfd_open(pid as libc::pid_t) {
+            Ok(pidfd) => Some(pidfd),
+            Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => None,
+            Err(e) => {
+                // The child can't be waited for without a pidfd, so don't
+                // leave it running.
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
+        Ok(Self {
+            pid,
+            pidfd,
+            waiting: None,
+            status: None,
+            stdin: child.stdin.take().map(|stdin| ChildStdin(stdin.into())),
+            stdout: child.stdout.take().map(|stdout| ChildStdout(stdout.into())),
+            stderr: child.stderr.take().map(|stderr| ChildStderr(stderr.into())),
+        })
+    }
+
+    /// Returns the process id of the child.
+    pub fn id(&self) -> u32 {
+        self.pid
+    }
+
+    /// Sends `SIGKILL` to the child.
+    ///
+    /// The signal is sent through the pidfd, so it never reaches another
+    /// process that reuses the id. Does nothing if the child has been reaped.
+    ///
+    /// See also [`std::process::Child::kill`].
+    pub fn kill(&mut self) -> Result<()> {
+        if self.status.is_some() {
+            return Ok(());
+        }
+        match &self.pidfd {
+            Some(pidfd) => syscall::pidfd_send_signal(pidfd.as_fd(), libc::SIGKILL),
+            // The child is not reaped until it is waited for, so the id can't
+            // be reused yet.
+            None => syscall::kill(self.pid as libc::pid_t, libc::SIGKILL),
+        }
+    }
+
+    /// Waits for the child to exit, and reaps it.
+    ///
+    /// The standard input of the child is closed before waiting, so that the
+    /// child doesn't wait for more input.
+    ///
+    /// See also [`std::process::Child::wait`].
+    pub async fn wait(&mut self) -> Result<ExitStatus> {
+        drop(self.stdin.take());
+        if let Some(status) = self.status {
+            return Ok(status);
+        }
+        let status = match &self.pidfd {
+            Some(pidfd) => exit_status(&syscall::waitid(pidfd.as_fd()).await?),
+            None => {
+                let pid = self.pid as libc::pid_t;
+                let waiting = self.waiting.get_or_insert_with(|| syscall::waitpid(pid));
+                let res = waiting.await;
+                self.waiting = None;
+                ExitStatus::from_raw(res?)
+            }
+        };
+        self.status = Some(status);
+        Ok(status)
+    }
+
+    /// Reaps the child if it has exited, without waiting.
+    ///
+    /// See also [`std::process::Child::try_wait`].
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        if self.status.is_some() {
+            return Ok(self.status);
+        }
+        self.status = match &self.pidfd {
+            Some(pidfd) => syscall::try_waitid(pidfd.as_fd())?.map(|info| exit_status(&info)),
+            // A cancelled wait might still reap the child, so check it instead.
+            None => match &mut self.waiting {
+                Some(waiting) => match waiting.now_or_never() {
+                    Some(res) => {
+                        self.waiting = None;
+                        Some(ExitStatus::from_raw(res?))
+                    }
+                    None => None,
+                },
+                None => syscall::try_waitpid(self.pid as libc::pid_t)?.map(ExitStatus::from_raw),
+            },
+        };
+        Ok(self.status)
+    }
+
+    /// Waits for the child to exit, and collects its output.
+    ///
+    /// The standard output and error are read concurrently until the end, so
+    /// the child doesn't block on a full pipe.
+    ///
+    /// See also [`std::process::Child::wait_with_output`].
+    pub async fn wait_with_output(mut self) -> Result<Output> {
+        drop(self.stdin.take());
+        let (stdout, stderr) = futures::join!(
+            read_to_end(self.stdout.take()),
+            read_to_end(self.stderr.take())
+        );
+        let status = self.wait().await?;
+        Ok(Output {
+            status,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+}
+
+impl fmt::Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("pid", &self.pid)
+            .field("pidfd", &self.pidfd)
+            .field("status", &self.status)
+            .field("stdin", &self.stdin)
+            .field("stdout", &self.stdout)
+            .field("stderr", &self.stderr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// # Panics
+///
+/// Panics if the kernel doesn't support pidfds.
+#[doc(hidden)]
+impl AsFd for Child {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.pidfd
+            .as_ref()
+            .expect("pidfds are not supported")
+            .as_fd()
+    }
+}
+
+/// # Panics
+///
+/// Panics if the kernel doesn't support pidfds.
+impl AsRawFd for Child {
+    fn as_raw_fd(&self) -> RawFd {
+        self.as_fd().as_raw_fd()
+    }
+}
+
+/// The standard input of a child process.
+#[derive(Debug)]
+pub struct ChildStdin(OwnedFd);
+
+impl Write for ChildStdin {
+    type Write<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> Self::Write<'a> {
+        syscall::write(self.0.as_fd(), buf)
+    }
+}
+
+#[doc(hidden)]
+impl AsFd for ChildStdin {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for ChildStdin {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+/// The standard output of a child process.
+#[derive(Debug)]
+pub struct ChildStdout(OwnedFd);
+
+impl Read for ChildStdout {
+    type Read<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        syscall::read(self.0.as_fd(), buf)
+    }
+}
+
+#[doc(hidden)]
+impl AsFd for ChildStdout {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for ChildStdout {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+/// The standard error of a child process.
+#[derive(Debug)]
+pub struct ChildStderr(OwnedFd);
+
+impl Read for ChildStderr {
+    type Read<'a> = impl Future<Output = Result<usize>> + 'a;
+
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Self::Read<'a> {
+        syscall::read(self.0.as_fd(), buf)
+    }
+}
+
+#[doc(hidden)]
+impl AsFd for ChildStderr {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl AsRawFd for ChildStderr {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+/// Converts the result of `waitid` to the status returned by `waitpid`.
+fn exit_status(info: &libc::siginfo_t) -> ExitStatus {
+    let status = unsafe { info.si_status() };
+    let raw = match info.si_code {
+        libc::CLD_EXITED => (status & 0xff) << 8,
+        libc::CLD_DUMPED => status | 0x80,
+        _ => status,
+    };
+    ExitStatus::from_raw(raw)
+}
+
+async fn read_to_end<R: Read>(reader: Option<R>) -> Result<Vec<u8>> {
+    let Some(mut reader) = reader else {
+        return Ok(Vec::new());
+    };
+    let mut buf = Vec::new();
+    let mut len = 0;
+    loop {
+        if len == buf.len() {
+            buf.resize((len * 2).max(8 * 1024), 0);
+        }
+        match reader.read(&mut buf[len..]).await {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    buf.truncate(len);
+    Ok(buf)
+}
@@ -0,0 +1,167 @@
+use std::{ffi::OsStr, io::Result, path::Path, process};
+
+use super::{Child, ExitStatus, Output, Stdio};
+
+/// A builder for spawning processes.
+///
+/// This type is an async version of [`std::process::Command`].
+#[derive(Debug)]
+pub struct Command {
+    std: process::Command,
+    // Whether the standard streams are configured, since `output` only
+    // captures them by default.
+    has_stdin: bool,
+    has_stdout: bool,
+    has_stderr: bool,
+}
+
+impl Command {
+    /// See also [`std::process::Command::new`].
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Self {
+        Self::from(process::Command::new(program))
+    }
+
+    /// See also [`std::process::Command::arg`].
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.std.arg(arg);
+        self
+    }
+
+    /// See also [`std::process::Command::args`].
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    /// See also [`std::process::Command::env`].
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.env(key, val);
+        self
+    }
+
+    /// See also [`std::process::Command::envs`].
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.envs(vars);
+        self
+    }
+
+    /// See also [`std::process::Command::env_remove`].
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Self {
+        self.std.env_remove(key);
+        self
+    }
+
+    /// See also [`std::process::Command::env_clear`].
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.std.env_clear();
+        self
+    }
+
+    /// See also [`std::process::Command::current_dir`].
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.std.current_dir(dir);
+        self
+    }
+
+    /// See also [`std::process::Command::stdin`].
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stdin(cfg);
+        self.has_stdin = true;
+        self
+    }
+
+    /// See also [`std::process::Command::stdout`].
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stdout(cfg);
+        self.has_stdout = true;
+        self
+    }
+
+    /// See also [`std::process::Command::stderr`].
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.std.stderr(cfg);
+        self.has_stderr = true;
+        self
+    }
+
+    /// Spawns the command as a child process.
+    ///
+    /// See also [`std::process::Command::spawn`].
+    pub fn spawn(&mut self) -> Result<Child> {
+        Child::new(self.std.spawn()?)
+    }
+
+    /// Spawns the command as a child process, waits for it to exit, and
+    /// collects its output.
+    ///
+    /// By default, the standard output and error are captured, and the
+    /// standard input is empty. Streams that are configured explicitly are
+    /// respected.
+    ///
+    /// See also [`std::process::Command::output`].
+    pub async fn output(&mut self) -> Result<Output> {
+        self.spawn_output()?.wait_with_output().await
+    }
+
+    /// Spawns the command as a child process and waits for it to exit.
+    ///
+    /// See also [`std::process::Command::status`].
+    pub async fn status(&mut self) -> Result<ExitStatus> {
+        self.spawn()?.wait().await
+    }
+}
+
+impl Command {
+    /// Spawns the command with the default streams of [`Self::output`].
+    fn spawn_output(&mut self) -> Result<Child> {
+        if !self.has_stdin {
+            self.std.stdin(Stdio::null());
+        }
+        if !self.has_stdout {
+            self.std.stdout(Stdio::piped());
+        }
+        if !self.has_stderr {
+            self.std.stderr(Stdio::piped());
+        }
+        let child = self.spawn();
+        // Restore the defaults of `spawn`, so this command is left unchanged.
+        if !self.has_stdin {
+            self.std.stdin(Stdio::inherit());
+        }
+        if !self.has_stdout {
+            self.std.stdout(Stdio::inherit());
+        }
+        if !self.has_stderr {
+            self.std.stderr(Stdio::inherit());
+        }
+        child
+    }
+}
+
+/// Converts a [`std::process::Command`].
+///
+/// The standard streams configured on it are not known, so they are treated as
+/// unset by [`Command::output`].
+impl From<process::Command> for Command {
+    fn from(command: process::Command) -> Self {
+        Self {
+            std: command,
+            has_stdin: false,
+            has_stdout: false,
+            has_stderr: false,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Primitives for spawning and waiting for processes.
+//!
+//! This module is an async version of [`std::process`].
+
+pub use std::process::{ExitStatus, Output, Stdio};
+
+mod command;
+pub use command::Command;
+
+mod child;
+pub use child::{Child, ChildStderr, ChildStdin, ChildStdout};
@@ -0,0 +1,187 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    panic,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{ready, Context, Poll},
+    thread,
+    time::Duration,
+};
+
+use futures::{channel::oneshot, FutureExt};
+use log::trace;
+
+use crate::task::coop;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A pool of threads to run blocking functions.
+#[derive(Clone)]
+pub(super) struct BlockingPool(Arc<Inner>);
+
+struct Inner {
+    state: Mutex<State>,
+    condvar: Condvar,
+    max_threads: usize,
+    stack_size: usize,
+    keep_alive: Duration,
+}
+
+struct State {
+    jobs: VecDeque<Job>,
+    next_id: usize,
+    num_threads: usize,
+    num_idle: usize,
+    num_notify: usize,
+}
+
+impl BlockingPool {
+    pub(super) fn new(max_threads: usize, stack_size: usize) -> Self {
+        let state = State {
+            jobs: VecDeque::new(),
+            next_id: 0,
+            num_threads: 0,
+            num_idle: 0,
+            num_notify: 0,
+        };
+        let inner = Inner {
+            state: Mutex::new(state),
+            condvar: Condvar::new(),
+            max_threads: max_threads.max(1),
+            stack_size,
+            keep_alive: Duration::from_secs(10),
+        };
+        Self(Arc::new(inner))
+    }
+
+    /// Runs `f` on the pool and returns a future that resolves to its output.
+    ///
+    /// If `f` panics, the panic is propagated to the caller.
+    pub(super) fn spawn<F, R>(&self, f: F) -> Blocking<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.execute(Box::new(move || {
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+            let _ = tx.send(result);
+        }));
+        Blocking { rx }
+    }
+
+    /// Like [`Self::spawn`], but for functions that use memory borrowed by the
+    /// caller.
+    pub(super) fn unblock<F, R>(&self, f: F) -> Unblock<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        Unblock {
+            inner: self.spawn(f),
+            is_finished: false,
+        }
+    }
+
+    fn execute(&self, job: Job) {
+        let mut state = self.0.state.lock().unwrap();
+        state.jobs.push_back(job);
+        if state.num_idle > 0 {
+            // Hand the job to an idle thread.
+            state.num_idle -= 1;
+            state.num_notify += 1;
+            self.0.condvar.notify_one();
+        } else if state.num_threads < self.0.max_threads {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.num_threads += 1;
+            let pool = self.clone();
+            let thread_name = format!("photonio-blocking/{}", id);
+            trace!("launch {}", thread_name);
+            thread::Builder::new()
+                .name(thread_name)
+                .stack_size(self.0.stack_size)
+                .spawn(move || pool.run())
+                .expect("failed to spawn a blocking thread");
+        }
+    }
+
+    fn run(&self) {
+        let mut state = self.0.state.lock().unwrap();
+        loop {
+            while let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                job();
+                state = self.0.state.lock().unwrap();
+            }
+            state.num_idle += 1;
+            loop {
+                let (next, timeout) = self
+                    .0
+                    .condvar
+                    .wait_timeout(state, self.0.keep_alive)
+                    .unwrap();
+                state = next;
+                if state.num_notify > 0 {
+                    // The notifier has removed this thread from the idle ones.
+                    state.num_notify -= 1;
+                    break;
+                }
+                if timeout.timed_out() {
+                    // Exit if the thread has been idle for a while.
+                    state.num_idle -= 1;
+                    state.num_threads -= 1;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// A future that resolves to the output of a blocking function.
+pub(crate) struct Blocking<T> {
+    rx: oneshot::Receiver<thread::Result<T>>,
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The function might have finished before this is polled, so this can
+        // be ready without ever yielding.
+        let coop = ready!(coop::poll_proceed(cx));
+        self.rx.poll_unpin(cx).map(|result| {
+            coop.made_progress();
+            match result.expect("the blocking pool is shut down") {
+                Ok(output) => output,
+                Err(err) => panic::resume_unwind(err),
+            }
+        })
+    }
+}
+
+/// A future that resolves to the output of a blocking function that uses
+/// memory borrowed by the caller.
+pub(crate) struct Unblock<T> {
+    inner: Blocking<T>,
+    is_finished: bool,
+}
+
+impl<T> Drop for Unblock<T> {
+    fn drop(&mut self) {
+        // The function might still be using memory borrowed by the caller.
+        assert!(self.is_finished);
+    }
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.poll_unpin(cx).map(|output| {
+            self.is_finished = true;
+            output
+        })
+    }
+}
@@ -1,12 +1,24 @@
-use std::io::Result;
+use std::{io::Result, time::Duration};
 
 use super::{Runtime, Shared};
 
 /// Builds a [`Runtime`] with custom options.
+#[derive(Clone)]
 pub struct Builder {
     pub(super) num_threads: usize,
     pub(super) thread_stack_size: usize,
     pub(super) event_interval: usize,
+    pub(super) max_blocking_threads: usize,
+    pub(super) sq_entries: u32,
+    pub(super) cq_entries: Option<u32>,
+    pub(super) sqpoll: bool,
+    pub(super) sqpoll_idle: Option<Duration>,
+    pub(super) sqpoll_cpu: Option<u32>,
+    pub(super) iopoll: bool,
+    pub(super) coop_taskrun: bool,
+    pub(super) single_issuer: bool,
+    pub(super) defer_taskrun: bool,
+    pub(super) attach_wq: bool,
 }
 
 impl Builder {
@@ -16,6 +28,17 @@ impl Builder {
             num_threads: num_cpus::get(),
             thread_stack_size: 2 << 20,
             event_interval: 3,
+            max_blocking_threads: 512,
+            sq_entries: 4096,
+            cq_entries: None,
+            sqpoll: false,
+            sqpoll_idle: None,
+            sqpoll_cpu: None,
+            iopoll: false,
+            coop_taskrun: false,
+            single_issuer: false,
+            defer_taskrun: false,
+            attach_wq: false,
         }
     }
 
@@ -43,6 +66,111 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum number of threads to run blocking operations.
+    ///
+    /// Blocking threads are spawned on demand, and exit after being idle for
+    /// a while. The default value is 512.
+    pub fn max_blocking_threads(mut self, max_blocking_threads: usize) -> Self {
+        self.max_blocking_threads = max_blocking_threads;
+        self
+    }
+
+    /// Sets the number of entries in the submission queue of each worker.
+    ///
+    /// The kernel rounds it up to the next power of two. The default value is
+    /// 4096.
+    pub fn sq_entries(mut self, sq_entries: u32) -> Self {
+        self.sq_entries = sq_entries;
+        self
+    }
+
+    /// Sets the number of entries in the completion queue of each worker.
+    ///
+    /// The default value is twice the number of submission queue entries.
+    pub fn cq_entries(mut self, cq_entries: u32) -> Self {
+        self.cq_entries = Some(cq_entries);
+        self
+    }
+
+    /// Enables a kernel thread to poll the submission queue of each worker.
+    ///
+    /// See also `IORING_SETUP_SQPOLL` in `man io_uring_setup.2`.
+    pub fn sqpoll(mut self, sqpoll: bool) -> Self {
+        self.sqpoll = sqpoll;
+        self
+    }
+
+    /// Sets how long the kernel polling thread spins before it goes to sleep.
+    ///
+    /// This option only takes effect if [`Self::sqpoll`] is enabled. The
+    /// default value is decided by the kernel. Values longer than `u32::MAX`
+    /// milliseconds are capped to that.
+    pub fn sqpoll_idle(mut self, idle: Duration) -> Self {
+        self.sqpoll_idle = Some(idle);
+        self
+    }
+
+    /// Binds the kernel polling thread to the given CPU.
+    ///
+    /// This option only takes effect if [`Self::sqpoll`] is enabled.
+    pub fn sqpoll_cpu(mut self, cpu: u32) -> Self {
+        self.sqpoll_cpu = Some(cpu);
+        self
+    }
+
+    /// Enables busy-waiting for I/O completions.
+    ///
+    /// The kernel only supports this mode for reads and writes of files opened
+    /// with `O_DIRECT`, so each worker sets up a second ring with
+    /// `IORING_SETUP_IOPOLL` for them. Other operations, and files that don't
+    /// support polling, still use the main ring. Workers don't sleep while
+    /// polled operations are in flight.
+    ///
+    /// [`Self::build`] fails with
+    /// [`ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported) if the
+    /// kernel doesn't support this mode.
+    ///
+    /// See also `IORING_SETUP_IOPOLL` in `man io_uring_setup.2`.
+    pub fn iopoll(mut self, iopoll: bool) -> Self {
+        self.iopoll = iopoll;
+        self
+    }
+
+    /// Stops the kernel from interrupting workers to run completion work.
+    ///
+    /// See also `IORING_SETUP_COOP_TASKRUN` in `man io_uring_setup.2`.
+    pub fn coop_taskrun(mut self, coop_taskrun: bool) -> Self {
+        self.coop_taskrun = coop_taskrun;
+        self
+    }
+
+    /// Tells the kernel that only the worker submits to its own ring.
+    ///
+    /// See also `IORING_SETUP_SINGLE_ISSUER` in `man io_uring_setup.2`.
+    pub fn single_issuer(mut self, single_issuer: bool) -> Self {
+        self.single_issuer = single_issuer;
+        self
+    }
+
+    /// Defers completion work until a worker asks for completions.
+    ///
+    /// This option implies [`Self::single_issuer`].
+    ///
+    /// See also `IORING_SETUP_DEFER_TASKRUN` in `man io_uring_setup.2`.
+    pub fn defer_taskrun(mut self, defer_taskrun: bool) -> Self {
+        self.defer_taskrun = defer_taskrun;
+        self
+    }
+
+    /// Shares one kernel worker pool among all workers, instead of creating
+    /// one pool per worker.
+    ///
+    /// See also `IORING_SETUP_ATTACH_WQ` in `man io_uring_setup.2`.
+    pub fn attach_wq(mut self, attach_wq: bool) -> Self {
+        self.attach_wq = attach_wq;
+        self
+    }
+
     /// Creates a runtime with the specified options.
     pub fn build(self) -> Result<Runtime> {
         let shared = Shared::new(self)?;
@@ -1,55 +1,164 @@
 use std::{
     io::{Error, ErrorKind, Result},
-    os::unix::io::{AsRawFd, FromRawFd, OwnedFd},
+    os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
     sync::Arc,
 };
 
-use io_uring::{opcode, squeue, types, IoUring};
+use io_uring::{opcode, squeue, types, IoUring, Probe};
+
+use super::{Builder, Features};
 
 mod op;
 pub(super) use op::Op;
-
-mod optable;
-use optable::OpTable;
+use op::OpState;
 
 pub(super) struct Driver {
     io: IoUring,
-    table: OpTable,
+    iopoll: Option<Polled>,
     eventfd: Arc<OwnedFd>,
     eventbuf: [u8; 8],
+    eventvec: libc::iovec,
+    features: Features,
+    defer_taskrun: bool,
 }
 
 impl Driver {
-    pub(super) fn new(unpark: Unpark) -> Result<Self> {
-        let io = IoUring::new(4096)?;
+    pub(super) fn new(unpark: Unpark, builder: &Builder, attach_wq: Option<RawFd>) -> Result<Self> {
+        let mut flags = Vec::new();
+        let io = setup(builder, attach_wq, Some(&mut flags)).map_err(|e| {
+            // The kernel returns EINVAL for flags it doesn't know, but also
+            // for invalid sizes. Only blame the flags if the ring can be set up
+            // without them.
+            if e.kind() == ErrorKind::InvalidInput
+                && !flags.is_empty()
+                && setup(builder, None, None).is_ok()
+            {
+                let msg = format!(
+                    "io_uring setup with {} is not supported: {}",
+                    flags.join(" | "),
+                    e
+                );
+                Error::new(ErrorKind::Unsupported, msg)
+            } else {
+                e
+            }
+        })?;
+        let iopoll = if builder.iopoll {
+            let io = setup_iopoll(builder).map_err(|e| {
+                if e.kind() == ErrorKind::InvalidInput {
+                    let msg = format!("io_uring setup with IOPOLL is not supported: {}", e);
+                    Error::new(ErrorKind::Unsupported, msg)
+                } else {
+                    e
+                }
+            })?;
+            Some(Polled { io, inflight: 0 })
+        } else {
+            None
+        };
+        let mut probe = Probe::new();
+        // Building with `--cfg photonio_legacy` skips the probe, so that the
+        // fallbacks of unsupported operations can be tested on new kernels.
+        let features = match io.submitter().register_probe(&mut probe) {
+            Ok(()) if !cfg!(photonio_legacy) => Features::from_probe(&probe),
+            _ => Features::legacy(),
+        };
         Ok(Self {
             io,
-            table: OpTable::new(),
+            iopoll,
             eventfd: unpark.0,
             eventbuf: [0; 8],
+            eventvec: libc::iovec {
+                iov_base: std::ptr::null_mut(),
+                iov_len: 0,
+            },
+            features,
+            defer_taskrun: builder.defer_taskrun,
         })
     }
 
+    pub(super) fn features(&self) -> &Features {
+        &self.features
+    }
+
     pub(super) unsafe fn add(&mut self, sqe: squeue::Entry) -> Result<Op> {
-        let index = self.table.add();
-        assert!(index as u64 != Self::UNPARK_TOKEN);
-        self.push(sqe.user_data(index as u64))?;
-        Ok(Op::new(self.table.clone(), index))
+        let state = Arc::new(OpState::new());
+        let token = Arc::into_raw(state.clone());
+        if let Err(e) = self.push(sqe.user_data(token as u64)) {
+            drop(Arc::from_raw(token));
+            return Err(e);
+        }
+        Ok(Op::new(state))
+    }
+
+    /// Submits a read or write of a file opened with `O_DIRECT` to the IOPOLL
+    /// ring.
+    ///
+    /// Returns `None` if the runtime doesn't use IOPOLL. The operation
+    /// completes with `EOPNOTSUPP` if the file doesn't support polling.
+    pub(super) unsafe fn add_polled(&mut self, sqe: squeue::Entry) -> Result<Option<Op>> {
+        let Some(polled) = self.iopoll.as_mut() else {
+            return Ok(None);
+        };
+        let state = Arc::new(OpState::new());
+        let token = Arc::into_raw(state.clone());
+        if let Err(e) = polled.push(sqe.user_data(token as u64)) {
+            drop(Arc::from_raw(token));
+            return Err(e);
+        }
+        polled.inflight += 1;
+        Ok(Some(Op::new(state)))
+    }
+
+    /// Cancels an owned operation, which then completes with `ECANCELED` if it
+    /// is still in flight.
+    ///
+    /// The operation must be submitted by this driver.
+    pub(super) fn cancel(&mut self, op: Op) -> Result<()> {
+        if op.is_pending() {
+            // Kernels without `IORING_OP_ASYNC_CANCEL` can only cancel polls.
+            let sqe = if self.features.is_supported(opcode::AsyncCancel::CODE) {
+                opcode::AsyncCancel::new(op.user_data()).build()
+            } else {
+                opcode::PollRemove::new(op.user_data()).build()
+            };
+            unsafe {
+                self.push(sqe.user_data(Self::CANCEL_TOKEN))?;
+            }
+        }
+        Ok(())
     }
 
     pub(super) fn tick(&mut self) -> Result<()> {
         self.submit()?;
         self.pull();
+        if let Some(polled) = self.iopoll.as_mut() {
+            polled.poll()?;
+        }
         Ok(())
     }
 
     pub(super) fn park(&mut self) -> Result<()> {
+        // Polled operations only complete when they are polled, so busy-wait
+        // instead of sleeping until they are done.
+        if self
+            .iopoll
+            .as_ref()
+            .map_or(false, |polled| polled.inflight > 0)
+        {
+            return self.tick();
+        }
         // Register the eventfd to unpark this driver.
         let fd = types::Fd(self.eventfd.as_raw_fd());
         let buf = &mut self.eventbuf;
-        let sqe = opcode::Read::new(fd, buf.as_mut_ptr(), buf.len() as _)
-            .build()
-            .user_data(Self::UNPARK_TOKEN);
+        let sqe = if self.features.is_supported(opcode::Read::CODE) {
+            opcode::Read::new(fd, buf.as_mut_ptr(), buf.len() as _).build()
+        } else {
+            self.eventvec.iov_base = buf.as_mut_ptr() as _;
+            self.eventvec.iov_len = buf.len();
+            opcode::Readv::new(fd, &self.eventvec, 1).build()
+        };
+        let sqe = sqe.user_data(Self::UNPARK_TOKEN);
         unsafe {
             self.push(sqe)?;
         }
@@ -61,6 +170,7 @@ impl Driver {
 
 impl Driver {
     const UNPARK_TOKEN: u64 = u64::MAX;
+    const CANCEL_TOKEN: u64 = u64::MAX - 1;
 
     unsafe fn push(&mut self, sqe: squeue::Entry) -> Result<()> {
         while {
@@ -75,24 +185,35 @@ impl Driver {
     }
 
     fn pull(&mut self) {
-        let mut cq = self.io.completion();
-        cq.sync();
-        for cqe in cq {
-            let token = cqe.user_data();
-            if token != Self::UNPARK_TOKEN {
-                let result = syscall_result(cqe.result());
-                self.table.complete(token as _, result);
-            }
-        }
+        complete(&mut self.io);
     }
 
     fn submit(&mut self) -> Result<usize> {
         self.submit_and_wait(0)
     }
 
+    fn enter(&mut self, want: usize) -> Result<usize> {
+        if self.defer_taskrun && want == 0 {
+            // Deferred completions are only processed when the kernel is asked
+            // for events, so ask for them even if we don't want to wait.
+            const IORING_ENTER_GETEVENTS: u32 = 1;
+            let len = self.io.submission().len();
+            unsafe {
+                self.io.submitter().enter::<libc::sigset_t>(
+                    len as _,
+                    0,
+                    IORING_ENTER_GETEVENTS,
+                    None,
+                )
+            }
+        } else {
+            self.io.submit_and_wait(want)
+        }
+    }
+
     fn submit_and_wait(&mut self, want: usize) -> Result<usize> {
         loop {
-            match self.io.submit_and_wait(want) {
+            match self.enter(want) {
                 Ok(n) => {
                     self.io.submission().sync();
                     return Ok(n);
@@ -109,6 +230,125 @@ impl Driver {
     }
 }
 
+/// A ring set up with `IORING_SETUP_IOPOLL`.
+///
+/// The kernel only supports reads and writes of files opened with `O_DIRECT`
+/// on such a ring, so it is kept apart from the ring for other operations.
+struct Polled {
+    io: IoUring,
+    inflight: usize,
+}
+
+impl Polled {
+    unsafe fn push(&mut self, sqe: squeue::Entry) -> Result<()> {
+        while {
+            let mut sq = self.io.submission();
+            sq.push(&sqe)
+        }
+        .is_err()
+        {
+            self.poll()?;
+        }
+        Ok(())
+    }
+
+    /// Submits pending operations and polls for their completions.
+    fn poll(&mut self) -> Result<()> {
+        if self.inflight == 0 {
+            return Ok(());
+        }
+        // Entering the ring polls for completions even if we don't wait.
+        match self.io.submit() {
+            Ok(_) => {}
+            // Both are retried on the next poll.
+            Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ResourceBusy) => {}
+            Err(e) => return Err(e),
+        }
+        self.inflight -= complete(&mut self.io);
+        Ok(())
+    }
+}
+
+/// Completes the operations of the entries in the completion queue of `io`.
+///
+/// Returns the number of completed operations.
+fn complete(io: &mut IoUring) -> usize {
+    let mut cq = io.completion();
+    cq.sync();
+    let mut n = 0;
+    for cqe in cq {
+        let token = cqe.user_data();
+        if token != Driver::UNPARK_TOKEN && token != Driver::CANCEL_TOKEN {
+            // Safety: the token is created by `Arc::into_raw` in `add` or
+            // `add_polled`.
+            let state = unsafe { Arc::from_raw(token as *const OpState) };
+            state.complete(cqe.result());
+            n += 1;
+        }
+    }
+    n
+}
+
+/// Sets up a ring with `IORING_SETUP_IOPOLL` for direct I/O.
+fn setup_iopoll(builder: &Builder) -> Result<IoUring> {
+    let mut io = IoUring::builder();
+    if let Some(cq_entries) = builder.cq_entries {
+        io.setup_cqsize(cq_entries);
+    }
+    io.setup_iopoll();
+    io.build(builder.sq_entries)
+}
+
+/// Sets up a ring with the options of `builder`.
+///
+/// If `flags` is `None`, the ring is set up without any flag. Otherwise, the
+/// names of the flags are pushed to it.
+fn setup(
+    builder: &Builder,
+    attach_wq: Option<RawFd>,
+    mut flags: Option<&mut Vec<&'static str>>,
+) -> Result<IoUring> {
+    let mut io = IoUring::builder();
+    if let Some(cq_entries) = builder.cq_entries {
+        io.setup_cqsize(cq_entries);
+    }
+    if let Some(flags) = flags.as_mut() {
+        if builder.sqpoll {
+            let idle = builder
+                .sqpoll_idle
+                .map_or(0, |idle| idle.as_millis().try_into().unwrap_or(u32::MAX));
+            io.setup_sqpoll(idle);
+            if let Some(cpu) = builder.sqpoll_cpu {
+                io.setup_sqpoll_cpu(cpu);
+            }
+            flags.push("SQPOLL");
+        }
+        if builder.coop_taskrun {
+            io.setup_coop_taskrun();
+            flags.push("COOP_TASKRUN");
+        }
+        if builder.single_issuer || builder.defer_taskrun {
+            io.setup_single_issuer();
+            flags.push("SINGLE_ISSUER");
+        }
+        if builder.defer_taskrun {
+            io.setup_defer_taskrun();
+            flags.push("DEFER_TASKRUN");
+        }
+        if let Some(fd) = attach_wq {
+            io.setup_attach_wq(fd);
+            flags.push("ATTACH_WQ");
+        }
+    }
+    io.build(builder.sq_entries)
+}
+
+impl AsRawFd for Driver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct Unpark(Arc<OwnedFd>);
 
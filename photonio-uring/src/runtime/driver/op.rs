@@ -2,30 +2,104 @@ use std::{
     future::Future,
     io::Result,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
 };
 
-use super::OpTable;
+use futures::task::AtomicWaker;
+
+use super::syscall_result;
+use crate::task::coop;
+
+/// The state shared by an operation and the driver that submits it.
+///
+/// The driver owns a reference to the state while the operation is in flight,
+/// which is passed to the kernel as the user data of the submission entry.
+/// This avoids a shared table of operations, so neither submissions nor
+/// completions need any lock.
+pub(super) struct OpState {
+    result: AtomicU64,
+    waker: AtomicWaker,
+}
+
+impl OpState {
+    const PENDING: u64 = u64::MAX;
+
+    pub(super) fn new() -> Self {
+        Self {
+            result: AtomicU64::new(Self::PENDING),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Completes the operation with the result of a completion entry.
+    pub(super) fn complete(&self, result: i32) {
+        self.result.store(result as u32 as u64, Ordering::Release);
+        self.waker.wake();
+    }
+
+    fn poll(&self, cx: &mut Context<'_>) -> Poll<i32> {
+        let result = self.result.load(Ordering::Acquire);
+        if result != Self::PENDING {
+            return Poll::Ready(result as u32 as i32);
+        }
+        self.waker.register(cx.waker());
+        // Check again in case the operation is completed before the waker is
+        // registered.
+        let result = self.result.load(Ordering::Acquire);
+        if result != Self::PENDING {
+            Poll::Ready(result as u32 as i32)
+        } else {
+            Poll::Pending
+        }
+    }
+}
 
 pub(crate) struct Op {
-    table: OpTable,
-    index: usize,
+    state: Arc<OpState>,
     is_finished: bool,
+    is_owned: bool,
 }
 
 impl Op {
-    pub(super) fn new(table: OpTable, index: usize) -> Self {
+    pub(super) fn new(state: Arc<OpState>) -> Self {
         Self {
-            table,
-            index,
+            state,
             is_finished: false,
+            is_owned: false,
         }
     }
+
+    /// Allows this operation to be dropped before it completes.
+    ///
+    /// # Safety
+    ///
+    /// The operation must not borrow any memory, since the kernel might still
+    /// access it after this is dropped.
+    pub(in crate::runtime) unsafe fn set_owned(&mut self) {
+        self.is_owned = true;
+    }
+
+    /// Returns true if the completion of this operation has not been received.
+    ///
+    /// The driver keeps the state alive until then, so the user data of the
+    /// operation is not reused by another one.
+    pub(in crate::runtime) fn is_pending(&self) -> bool {
+        self.state.result.load(Ordering::Acquire) == OpState::PENDING
+    }
+
+    /// Returns the user data of the submission entry of this operation.
+    pub(super) fn user_data(&self) -> u64 {
+        Arc::as_ptr(&self.state) as u64
+    }
 }
 
 impl Drop for Op {
     fn drop(&mut self) {
-        assert!(self.is_finished);
+        assert!(self.is_finished || self.is_owned);
     }
 }
 
@@ -33,10 +107,11 @@ impl Future for Op {
     type Output = Result<u32>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        let index = self.index;
-        self.table.poll(index, cx.waker()).map(|v| {
+        let coop = ready!(coop::poll_proceed(cx));
+        self.state.poll(cx).map(|v| {
+            coop.made_progress();
             self.is_finished = true;
-            v
+            syscall_result(v)
         })
     }
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use io_uring::{opcode, Probe};
+
+/// The io_uring features supported by the kernel.
+///
+/// Operations that are not supported by the kernel are executed on a blocking
+/// thread pool instead.
+#[derive(Clone)]
+pub struct Features {
+    ops: [u64; 4],
+}
+
+impl Features {
+    /// Returns true if the kernel supports the given io_uring opcode.
+    ///
+    /// See `IORING_OP_*` in `man io_uring_enter.2` for the list of opcodes.
+    pub fn is_supported(&self, opcode: u8) -> bool {
+        self.ops[opcode as usize / 64] & (1 << (opcode % 64)) != 0
+    }
+}
+
+impl Features {
+    pub(super) fn from_probe(probe: &Probe) -> Self {
+        let mut features = Self { ops: [0; 4] };
+        for opcode in 0..=u8::MAX {
+            if probe.is_supported(opcode) {
+                features.add(opcode);
+            }
+        }
+        features
+    }
+
+    /// Returns the features supported by kernels without
+    /// `IORING_REGISTER_PROBE`.
+    pub(super) fn legacy() -> Self {
+        let mut features = Self { ops: [0; 4] };
+        for opcode in [
+            opcode::Nop::CODE,
+            opcode::Readv::CODE,
+            opcode::Writev::CODE,
+            opcode::Fsync::CODE,
+            opcode::PollAdd::CODE,
+            opcode::PollRemove::CODE,
+        ] {
+            features.add(opcode);
+        }
+        features
+    }
+
+    fn add(&mut self, opcode: u8) {
+        self.ops[opcode as usize / 64] |= 1 << (opcode % 64);
+    }
+}
+
+impl fmt::Debug for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ops = (0..=u8::MAX).filter(|&opcode| self.is_supported(opcode));
+        f.debug_struct("Features")
+            .field("ops", &ops.collect::<Vec<_>>())
+            .finish()
+    }
+}
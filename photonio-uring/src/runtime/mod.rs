@@ -15,7 +15,13 @@ use shared::Shared;
 mod driver;
 
 mod worker;
-pub use worker::spawn;
+pub use worker::{features, spawn};
+
+mod blocking;
+pub(crate) use blocking::Blocking;
+
+mod features;
+pub use features::Features;
 
 pub(crate) mod syscall;
 
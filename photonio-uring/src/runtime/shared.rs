@@ -9,7 +9,11 @@ use std::{
 
 use log::trace;
 
-use super::{worker::Worker, Builder};
+use super::{
+    blocking::{Blocking, BlockingPool, Unblock},
+    worker::Worker,
+    Builder,
+};
 use crate::task::JoinHandle;
 
 #[derive(Clone)]
@@ -18,6 +22,7 @@ pub(super) struct Shared(Arc<Inner>);
 struct Inner {
     workers: Vec<Worker>,
     next_id: AtomicU64,
+    blocking: BlockingPool,
 }
 
 impl Shared {
@@ -30,14 +35,16 @@ impl Shared {
         let inner = Inner {
             workers,
             next_id: AtomicU64::new(0),
+            blocking: BlockingPool::new(builder.max_blocking_threads, builder.thread_stack_size),
         };
         let shared = Self(Arc::new(inner));
+        let mut attach_wq = None;
         for worker in &shared.0.workers {
-            worker.launch(
-                shared.clone(),
-                builder.thread_stack_size,
-                builder.event_interval,
-            )?;
+            let fd = worker.launch(shared.clone(), &builder, attach_wq)?;
+            // Attach all other workers to the kernel worker pool of the first one.
+            if builder.attach_wq && attach_wq.is_none() {
+                attach_wq = Some(fd);
+            }
         }
         Ok(shared)
     }
@@ -53,4 +60,20 @@ impl Shared {
         trace!("dispatch task {} to worker {}", id, index);
         self.0.workers[index].schedule(id, future)
     }
+
+    pub(super) fn spawn_blocking<F, R>(&self, f: F) -> Blocking<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.0.blocking.spawn(f)
+    }
+
+    pub(super) fn unblock<F, R>(&self, f: F) -> Unblock<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.0.blocking.unblock(f)
+    }
 }
@@ -1,51 +1,160 @@
 //! Asynchronous system calls.
+//!
+//! Operations that are not supported by the kernel's io_uring are executed on
+//! the blocking pool instead.
 
 use std::{
-    ffi::CString,
+    ffi::{CStr, CString, OsStr, OsString},
     io::{Error, ErrorKind, Result},
     mem,
     os::unix::{
-        ffi::OsStrExt,
-        io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+        ffi::{OsStrExt, OsStringExt},
+        io::{AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
     },
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
-use io_uring::{opcode, types};
+use io_uring::{opcode, squeue, types};
 use socket2::SockAddr;
 
-use super::worker::submit;
+use super::{
+    worker::{is_supported, spawn_blocking, submit, submit_owned, submit_polled, unblock},
+    Blocking,
+};
 
 /// See also `man open.2`.
 pub(crate) async fn open(path: &Path, flags: libc::c_int, mode: libc::mode_t) -> Result<OwnedFd> {
+    open_inner(libc::AT_FDCWD, path, flags, mode).await
+}
+
+/// See also `man openat.2`.
+pub(crate) async fn openat(
+    dirfd: BorrowedFd<'_>,
+    path: &Path,
+    flags: libc::c_int,
+    mode: libc::mode_t,
+) -> Result<OwnedFd> {
+    open_inner(dirfd.as_raw_fd(), path, flags, mode).await
+}
+
+async fn open_inner(
+    dirfd: RawFd,
+    path: &Path,
+    flags: libc::c_int,
+    mode: libc::mode_t,
+) -> Result<OwnedFd> {
     let path = new_path_str(path)?;
-    let sqe = opcode::OpenAt::new(types::Fd(libc::AT_FDCWD), path.as_c_str().as_ptr())
-        .flags(flags | libc::O_CLOEXEC)
-        .mode(mode)
-        .build();
-    submit(sqe)?
-        .await
-        .map(|fd| unsafe { OwnedFd::from_raw_fd(fd as _) })
+    let flags = flags | libc::O_CLOEXEC;
+    let fd = if is_supported(opcode::OpenAt::CODE) {
+        let sqe = opcode::OpenAt::new(types::Fd(dirfd), path.as_c_str().as_ptr())
+            .flags(flags)
+            .mode(mode)
+            .build();
+        submit(sqe)?.await?
+    } else {
+        unblock(move || cvt(unsafe { libc::openat(dirfd, path.as_ptr(), flags, mode) })).await?
+    };
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as _) })
 }
 
 /// See also `man close.2`.
-#[allow(dead_code)]
 pub(crate) async fn close(fd: OwnedFd) -> Result<()> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Close::new(fd).build();
-    submit(sqe)?.await.map(|_| ())
+    if is_supported(opcode::Close::CODE) {
+        let fd = types::Fd(fd.into_raw_fd());
+        let sqe = opcode::Close::new(fd).build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        let fd = fd.into_raw_fd();
+        unblock(move || cvt(unsafe { libc::close(fd) }))
+            .await
+            .map(|_| ())
+    }
 }
 
 /// See also `man fstat.2`.
 pub(crate) async fn fstat(fd: BorrowedFd<'_>) -> Result<libc::statx> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let path = CString::new("").unwrap();
-    let mut stat = unsafe { mem::zeroed() };
-    let sqe = opcode::Statx::new(fd, path.as_ptr(), &mut stat as *mut _ as *mut _)
-        .flags(libc::AT_EMPTY_PATH)
-        .mask(libc::STATX_ALL)
+    fstatx(fd, libc::STATX_ALL).await
+}
+
+/// Like [`fstat`], but only queries the fields in `mask`.
+pub(crate) async fn fstatx(fd: BorrowedFd<'_>, mask: libc::c_uint) -> Result<libc::statx> {
+    statx_inner(
+        fd.as_raw_fd(),
+        CString::default(),
+        libc::AT_EMPTY_PATH,
+        mask,
+    )
+    .await
+}
+
+/// See also `man stat.2`.
+pub(crate) async fn stat(path: &Path, flags: libc::c_int) -> Result<libc::statx> {
+    let path = new_path_str(path)?;
+    statx_inner(libc::AT_FDCWD, path, flags, libc::STATX_ALL).await
+}
+
+/// See also `man statx.2`.
+pub(crate) async fn statx(
+    dirfd: BorrowedFd<'_>,
+    path: &Path,
+    flags: libc::c_int,
+) -> Result<libc::statx> {
+    let path = new_path_str(path)?;
+    statx_inner(dirfd.as_raw_fd(), path, flags, libc::STATX_ALL).await
+}
+
+async fn statx_inner(
+    dirfd: RawFd,
+    path: CString,
+    flags: libc::c_int,
+    mask: libc::c_uint,
+) -> Result<libc::statx> {
+    if is_supported(opcode::Statx::CODE) {
+        let mut stat = unsafe { mem::zeroed() };
+        let sqe = opcode::Statx::new(
+            types::Fd(dirfd),
+            path.as_ptr(),
+            &mut stat as *mut _ as *mut _,
+        )
+        .flags(flags)
+        .mask(mask)
         .build();
-    submit(sqe)?.await.map(|_| stat)
+        submit(sqe)?.await.map(|_| stat)
+    } else {
+        unblock(move || {
+            let mut stat = unsafe { mem::zeroed() };
+            cvt(unsafe { libc::statx(dirfd, path.as_ptr(), flags, mask, &mut stat) }).map(|_| stat)
+        })
+        .await
+    }
+}
+
+/// See also `man statfs.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn statfs(path: &Path) -> Result<libc::statfs64> {
+    let path = new_path_str(path)?;
+    unblock(move || {
+        let mut stat = unsafe { mem::zeroed() };
+        cvt(unsafe { libc::statfs64(path.as_ptr(), &mut stat) }).map(|_| stat)
+    })
+    .await
+}
+
+/// See also `man fstatfs.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn fstatfs(fd: BorrowedFd<'_>) -> Result<libc::statfs64> {
+    let fd = fd.as_raw_fd();
+    unblock(move || {
+        let mut stat = unsafe { mem::zeroed() };
+        cvt(unsafe { libc::fstatfs64(fd, &mut stat) }).map(|_| stat)
+    })
+    .await
 }
 
 /// See also `man fsync.2`.
@@ -59,61 +168,612 @@ pub(crate) async fn fdatasync(fd: BorrowedFd<'_>) -> Result<()> {
 }
 
 async fn fsync_inner(fd: BorrowedFd<'_>, flags: types::FsyncFlags) -> Result<()> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Fsync::new(fd).flags(flags).build();
-    submit(sqe)?.await.map(|_| ())
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Fsync::CODE) {
+        let sqe = opcode::Fsync::new(types::Fd(fd)).flags(flags).build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || {
+            if flags.contains(types::FsyncFlags::DATASYNC) {
+                cvt(unsafe { libc::fdatasync(fd) })
+            } else {
+                cvt(unsafe { libc::fsync(fd) })
+            }
+        })
+        .await
+        .map(|_| ())
+    }
+}
+
+/// See also `man fallocate.2`.
+pub(crate) async fn fallocate(
+    fd: BorrowedFd<'_>,
+    mode: libc::c_int,
+    offset: libc::off64_t,
+    len: libc::off64_t,
+) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Fallocate64::CODE) {
+        let sqe = opcode::Fallocate64::new(types::Fd(fd), len)
+            .offset64(offset)
+            .mode(mode)
+            .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::fallocate64(fd, mode, offset, len) }))
+            .await
+            .map(|_| ())
+    }
+}
+
+/// See also `man ftruncate.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn ftruncate(fd: BorrowedFd<'_>, len: libc::off64_t) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    unblock(move || cvt(unsafe { libc::ftruncate64(fd, len) }))
+        .await
+        .map(|_| ())
+}
+
+/// See also `man sync_file_range.2`.
+pub(crate) async fn sync_file_range(
+    fd: BorrowedFd<'_>,
+    offset: libc::off64_t,
+    len: u32,
+    flags: libc::c_uint,
+) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::SyncFileRange::CODE) {
+        let sqe = opcode::SyncFileRange::new(types::Fd(fd), len)
+            .offset(offset)
+            .flags(flags)
+            .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::sync_file_range(fd, offset, len.into(), flags) }))
+            .await
+            .map(|_| ())
+    }
+}
+
+/// See also `man posix_fadvise.2`.
+pub(crate) async fn fadvise(
+    fd: BorrowedFd<'_>,
+    offset: libc::off64_t,
+    len: libc::off64_t,
+    advice: libc::c_int,
+) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    // The length of the operation is limited to 32 bits.
+    if is_supported(opcode::Fadvise::CODE) && len <= u32::MAX.into() {
+        let sqe = opcode::Fadvise::new(types::Fd(fd), len, advice)
+            .offset64(offset)
+            .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(
+            move || match unsafe { libc::posix_fadvise64(fd, offset, len, advice) } {
+                0 => Ok(()),
+                err => Err(Error::from_raw_os_error(err)),
+            },
+        )
+        .await
+    }
+}
+
+/// See also `man readahead.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn readahead(fd: BorrowedFd<'_>, offset: libc::off64_t, len: usize) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    unblock(move || cvt(unsafe { libc::readahead(fd, offset, len) } as i64))
+        .await
+        .map(|_| ())
+}
+
+/// See also `man mmap.2`.
+///
+/// This operation doesn't wait for I/O, so it runs on the current thread.
+pub(crate) fn mmap(
+    fd: BorrowedFd<'_>,
+    len: usize,
+    prot: libc::c_int,
+    offset: libc::off64_t,
+) -> Result<*mut u8> {
+    let ptr = unsafe {
+        libc::mmap64(
+            std::ptr::null_mut(),
+            len,
+            prot,
+            libc::MAP_SHARED,
+            fd.as_raw_fd(),
+            offset,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        Err(Error::last_os_error())
+    } else {
+        Ok(ptr as *mut u8)
+    }
+}
+
+/// See also `man munmap.2`.
+///
+/// # Safety
+///
+/// The memory must be a mapping that is not used after this call.
+pub(crate) unsafe fn munmap(ptr: *mut u8, len: usize) -> Result<()> {
+    cvt(libc::munmap(ptr as *mut libc::c_void, len)).map(drop)
+}
+
+/// See also `man madvise.2`.
+pub(crate) fn madvise(buf: &[u8], advice: libc::c_int) -> Result<()> {
+    let ptr = buf.as_ptr() as *mut libc::c_void;
+    cvt(unsafe { libc::madvise(ptr, buf.len(), advice) }).map(drop)
+}
+
+/// See also `man mlock.2`.
+///
+/// Locking faults in the memory, so it always runs on the blocking pool.
+pub(crate) async fn mlock(buf: &[u8]) -> Result<()> {
+    let len = buf.len();
+    let buf = SendPtr(buf.as_ptr() as *mut u8);
+    unblock(move || cvt(unsafe { libc::mlock(buf.get() as *const libc::c_void, len) }))
+        .await
+        .map(drop)
+}
+
+/// See also `man munlock.2`.
+pub(crate) fn munlock(buf: &[u8]) -> Result<()> {
+    let ptr = buf.as_ptr() as *const libc::c_void;
+    cvt(unsafe { libc::munlock(ptr, buf.len()) }).map(drop)
+}
+
+/// See also `man msync.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn msync(buf: &[u8], flags: libc::c_int) -> Result<()> {
+    let len = buf.len();
+    let buf = SendPtr(buf.as_ptr() as *mut u8);
+    unblock(move || cvt(unsafe { libc::msync(buf.get() as *mut libc::c_void, len, flags) }))
+        .await
+        .map(drop)
+}
+
+/// See also `man inotify_init1.2`.
+///
+/// The instance is in non-blocking mode, so it can be read with [`try_read`].
+pub(crate) fn inotify_init() -> Result<OwnedFd> {
+    let fd = cvt(unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// See also `man inotify_add_watch.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn inotify_add_watch(
+    fd: BorrowedFd<'_>,
+    path: &Path,
+    mask: u32,
+) -> Result<libc::c_int> {
+    let fd = fd.as_raw_fd();
+    let path = new_path_str(path)?;
+    unblock(move || cvt(unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) }))
+        .await
+        .map(|wd| wd as libc::c_int)
+}
+
+/// See also `man inotify_rm_watch.2`.
+pub(crate) fn inotify_rm_watch(fd: BorrowedFd<'_>, wd: libc::c_int) -> Result<()> {
+    cvt(unsafe { libc::inotify_rm_watch(fd.as_raw_fd(), wd) }).map(drop)
 }
 
 /// See also `man mkdir.2`.
 pub(crate) async fn mkdir(path: &Path, mode: libc::mode_t) -> Result<()> {
+    mkdir_inner(libc::AT_FDCWD, path, mode).await
+}
+
+/// See also `man mkdirat.2`.
+pub(crate) async fn mkdirat(dirfd: BorrowedFd<'_>, path: &Path, mode: libc::mode_t) -> Result<()> {
+    mkdir_inner(dirfd.as_raw_fd(), path, mode).await
+}
+
+async fn mkdir_inner(dirfd: RawFd, path: &Path, mode: libc::mode_t) -> Result<()> {
     let path = new_path_str(path)?;
-    let sqe = opcode::MkDirAt::new(types::Fd(libc::AT_FDCWD), path.as_c_str().as_ptr())
-        .mode(mode)
-        .build();
-    submit(sqe)?.await.map(|_| ())
+    if is_supported(opcode::MkDirAt::CODE) {
+        let sqe = opcode::MkDirAt::new(types::Fd(dirfd), path.as_c_str().as_ptr())
+            .mode(mode)
+            .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::mkdirat(dirfd, path.as_ptr(), mode) }))
+            .await
+            .map(|_| ())
+    }
 }
 
 /// See also `man rmdir.2`.
 pub(crate) async fn rmdir(path: &Path) -> Result<()> {
-    unlink_inner(path, libc::AT_REMOVEDIR).await
+    unlink_inner(libc::AT_FDCWD, path, libc::AT_REMOVEDIR).await
 }
 
 /// See also `man unlink.2`.
 pub(crate) async fn unlink(path: &Path) -> Result<()> {
-    unlink_inner(path, 0).await
+    unlink_inner(libc::AT_FDCWD, path, 0).await
+}
+
+/// See also `man unlinkat.2`.
+pub(crate) async fn unlinkat(dirfd: BorrowedFd<'_>, path: &Path, flags: libc::c_int) -> Result<()> {
+    unlink_inner(dirfd.as_raw_fd(), path, flags).await
 }
 
-async fn unlink_inner(path: &Path, flags: libc::c_int) -> Result<()> {
+async fn unlink_inner(dirfd: RawFd, path: &Path, flags: libc::c_int) -> Result<()> {
     let path = new_path_str(path)?;
-    let sqe = opcode::UnlinkAt::new(types::Fd(libc::AT_FDCWD), path.as_c_str().as_ptr())
+    if is_supported(opcode::UnlinkAt::CODE) {
+        let sqe = opcode::UnlinkAt::new(types::Fd(dirfd), path.as_c_str().as_ptr())
+            .flags(flags)
+            .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::unlinkat(dirfd, path.as_ptr(), flags) }))
+            .await
+            .map(|_| ())
+    }
+}
+
+/// See also `man rename.2`.
+pub(crate) async fn rename(oldpath: &Path, newpath: &Path, flags: libc::c_uint) -> Result<()> {
+    rename_inner(libc::AT_FDCWD, oldpath, libc::AT_FDCWD, newpath, flags).await
+}
+
+/// See also `man renameat2.2`.
+pub(crate) async fn renameat(
+    olddirfd: BorrowedFd<'_>,
+    oldpath: &Path,
+    newdirfd: BorrowedFd<'_>,
+    newpath: &Path,
+    flags: libc::c_uint,
+) -> Result<()> {
+    let olddirfd = olddirfd.as_raw_fd();
+    let newdirfd = newdirfd.as_raw_fd();
+    rename_inner(olddirfd, oldpath, newdirfd, newpath, flags).await
+}
+
+async fn rename_inner(
+    olddirfd: RawFd,
+    oldpath: &Path,
+    newdirfd: RawFd,
+    newpath: &Path,
+    flags: libc::c_uint,
+) -> Result<()> {
+    let oldpath = new_path_str(oldpath)?;
+    let newpath = new_path_str(newpath)?;
+    if is_supported(opcode::RenameAt::CODE) {
+        let sqe = opcode::RenameAt::new(
+            types::Fd(olddirfd),
+            oldpath.as_c_str().as_ptr(),
+            types::Fd(newdirfd),
+            newpath.as_c_str().as_ptr(),
+        )
         .flags(flags)
         .build();
-    submit(sqe)?.await.map(|_| ())
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || {
+            cvt(unsafe {
+                libc::syscall(
+                    libc::SYS_renameat2,
+                    olddirfd,
+                    oldpath.as_ptr(),
+                    newdirfd,
+                    newpath.as_ptr(),
+                    flags,
+                )
+            })
+        })
+        .await
+        .map(|_| ())
+    }
 }
 
-/// See also `man rename.2`.
-pub(crate) async fn rename(oldpath: &Path, newpath: &Path) -> Result<()> {
+/// See also `man linkat.2`.
+pub(crate) async fn link(oldpath: &Path, newpath: &Path, flags: libc::c_int) -> Result<()> {
     let oldpath = new_path_str(oldpath)?;
     let newpath = new_path_str(newpath)?;
-    let sqe = opcode::RenameAt::new(
-        types::Fd(libc::AT_FDCWD),
-        oldpath.as_c_str().as_ptr(),
-        types::Fd(libc::AT_FDCWD),
-        newpath.as_c_str().as_ptr(),
-    )
-    .build();
-    submit(sqe)?.await.map(|_| ())
+    if is_supported(opcode::LinkAt::CODE) {
+        let sqe = opcode::LinkAt::new(
+            types::Fd(libc::AT_FDCWD),
+            oldpath.as_c_str().as_ptr(),
+            types::Fd(libc::AT_FDCWD),
+            newpath.as_c_str().as_ptr(),
+        )
+        .flags(flags)
+        .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || {
+            cvt(unsafe {
+                libc::linkat(
+                    libc::AT_FDCWD,
+                    oldpath.as_ptr(),
+                    libc::AT_FDCWD,
+                    newpath.as_ptr(),
+                    flags,
+                )
+            })
+        })
+        .await
+        .map(|_| ())
+    }
+}
+
+/// See also `man symlink.2`.
+pub(crate) async fn symlink(target: &Path, linkpath: &Path) -> Result<()> {
+    let target = new_path_str(target)?;
+    let linkpath = new_path_str(linkpath)?;
+    if is_supported(opcode::SymlinkAt::CODE) {
+        let sqe = opcode::SymlinkAt::new(
+            types::Fd(libc::AT_FDCWD),
+            target.as_c_str().as_ptr(),
+            linkpath.as_c_str().as_ptr(),
+        )
+        .build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || {
+            cvt(unsafe { libc::symlinkat(target.as_ptr(), libc::AT_FDCWD, linkpath.as_ptr()) })
+        })
+        .await
+        .map(|_| ())
+    }
+}
+
+/// See also `man readlink.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn readlink(path: &Path) -> Result<PathBuf> {
+    let path = new_path_str(path)?;
+    unblock(move || {
+        let mut buf = Vec::<u8>::with_capacity(256);
+        loop {
+            let cap = buf.capacity();
+            let ret = unsafe { libc::readlink(path.as_ptr(), buf.as_mut_ptr() as _, cap) };
+            let len = cvt(ret as i64)? as usize;
+            unsafe { buf.set_len(len) };
+            // The link might have been truncated if it fills the buffer.
+            if len < cap {
+                buf.shrink_to_fit();
+                return Ok(PathBuf::from(OsString::from_vec(buf)));
+            }
+            buf.reserve(1);
+        }
+    })
+    .await
+}
+
+/// See also `man realpath.3`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn realpath(path: &Path) -> Result<PathBuf> {
+    let path = new_path_str(path)?;
+    unblock(move || unsafe {
+        let ptr = libc::realpath(path.as_ptr(), std::ptr::null_mut());
+        if ptr.is_null() {
+            return Err(Error::last_os_error());
+        }
+        let buf = CStr::from_ptr(ptr).to_bytes().to_vec();
+        libc::free(ptr as _);
+        Ok(PathBuf::from(OsString::from_vec(buf)))
+    })
+    .await
+}
+
+/// See also `man chmod.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn chmod(path: &Path, mode: libc::mode_t) -> Result<()> {
+    let path = new_path_str(path)?;
+    unblock(move || cvt(unsafe { libc::fchmodat(libc::AT_FDCWD, path.as_ptr(), mode, 0) }))
+        .await
+        .map(|_| ())
+}
+
+/// See also `man chown.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn chown(
+    path: &Path,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+    flags: libc::c_int,
+) -> Result<()> {
+    let path = new_path_str(path)?;
+    unblock(move || cvt(unsafe { libc::fchownat(libc::AT_FDCWD, path.as_ptr(), uid, gid, flags) }))
+        .await
+        .map(|_| ())
+}
+
+/// See also `man fchown.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn fchown(fd: BorrowedFd<'_>, uid: libc::uid_t, gid: libc::gid_t) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    unblock(move || cvt(unsafe { libc::fchown(fd, uid, gid) }))
+        .await
+        .map(|_| ())
+}
+
+/// The file of an extended attribute operation.
+#[derive(Clone, Copy)]
+pub(crate) enum XattrTarget<'a> {
+    /// An open file.
+    Fd(BorrowedFd<'a>),
+    /// A path, which is resolved following symbolic links.
+    Path(&'a Path),
+}
+
+impl XattrTarget<'_> {
+    fn to_raw(self) -> Result<(RawFd, Option<CString>)> {
+        match self {
+            Self::Fd(fd) => Ok((fd.as_raw_fd(), None)),
+            Self::Path(path) => Ok((libc::AT_FDCWD, Some(new_path_str(path)?))),
+        }
+    }
+}
+
+/// See also `man getxattr.2`.
+pub(crate) async fn getxattr(
+    target: XattrTarget<'_>,
+    name: &OsStr,
+    buf: &mut [u8],
+) -> Result<usize> {
+    let (fd, path) = target.to_raw()?;
+    let name = new_name_str(name)?;
+    let opcode = match path {
+        Some(_) => IORING_OP_GETXATTR,
+        None => IORING_OP_FGETXATTR,
+    };
+    if is_supported(opcode) {
+        let sqe = RawEntry {
+            opcode,
+            fd,
+            addr: name.as_ptr() as u64,
+            addr2: buf.as_mut_ptr() as u64,
+            len: buf.len() as u32,
+            addr3: path.as_ref().map_or(0, |path| path.as_ptr() as u64),
+            ..Default::default()
+        };
+        submit(sqe.build())?.await.map(|n| n as usize)
+    } else {
+        let len = buf.len();
+        let buf = SendPtr(buf.as_mut_ptr());
+        unblock(move || {
+            let buf = buf.get() as *mut libc::c_void;
+            let ret = match path {
+                Some(path) => unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), buf, len) },
+                None => unsafe { libc::fgetxattr(fd, name.as_ptr(), buf, len) },
+            };
+            cvt(ret as i64).map(|n| n as usize)
+        })
+        .await
+    }
+}
+
+/// See also `man setxattr.2`.
+pub(crate) async fn setxattr(
+    target: XattrTarget<'_>,
+    name: &OsStr,
+    value: &[u8],
+    flags: libc::c_int,
+) -> Result<()> {
+    let (fd, path) = target.to_raw()?;
+    let name = new_name_str(name)?;
+    let opcode = match path {
+        Some(_) => IORING_OP_SETXATTR,
+        None => IORING_OP_FSETXATTR,
+    };
+    if is_supported(opcode) {
+        let sqe = RawEntry {
+            opcode,
+            fd,
+            addr: name.as_ptr() as u64,
+            addr2: value.as_ptr() as u64,
+            len: value.len() as u32,
+            op_flags: flags as u32,
+            addr3: path.as_ref().map_or(0, |path| path.as_ptr() as u64),
+            ..Default::default()
+        };
+        submit(sqe.build())?.await.map(|_| ())
+    } else {
+        let len = value.len();
+        let value = SendPtr(value.as_ptr() as *mut u8);
+        unblock(move || {
+            let value = value.get() as *const libc::c_void;
+            let ret = match path {
+                Some(path) => unsafe {
+                    libc::setxattr(path.as_ptr(), name.as_ptr(), value, len, flags)
+                },
+                None => unsafe { libc::fsetxattr(fd, name.as_ptr(), value, len, flags) },
+            };
+            cvt(ret).map(|_| ())
+        })
+        .await
+    }
+}
+
+/// See also `man removexattr.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn removexattr(target: XattrTarget<'_>, name: &OsStr) -> Result<()> {
+    let (fd, path) = target.to_raw()?;
+    let name = new_name_str(name)?;
+    unblock(move || {
+        let ret = match path {
+            Some(path) => unsafe { libc::removexattr(path.as_ptr(), name.as_ptr()) },
+            None => unsafe { libc::fremovexattr(fd, name.as_ptr()) },
+        };
+        cvt(ret).map(|_| ())
+    })
+    .await
+}
+
+/// See also `man listxattr.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn listxattr(target: XattrTarget<'_>, buf: &mut [u8]) -> Result<usize> {
+    let (fd, path) = target.to_raw()?;
+    let len = buf.len();
+    let buf = SendPtr(buf.as_mut_ptr());
+    unblock(move || {
+        let buf = buf.get() as *mut libc::c_char;
+        let ret = match path {
+            Some(path) => unsafe { libc::listxattr(path.as_ptr(), buf, len) },
+            None => unsafe { libc::flistxattr(fd, buf, len) },
+        };
+        cvt(ret as i64).map(|n| n as usize)
+    })
+    .await
 }
 
 /// See also `man accept.2`.
 pub(crate) async fn accept(fd: BorrowedFd<'_>) -> Result<(OwnedFd, SockAddr)> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
-    let mut addr_len = mem::size_of_val(&addr) as libc::socklen_t;
-    let sqe = opcode::Accept::new(fd, &mut addr as *mut _ as *mut _, &mut addr_len)
-        .flags(libc::O_CLOEXEC)
-        .build();
-    let conn = submit(sqe)?.await?;
+    let fd = fd.as_raw_fd();
+    let (conn, addr, addr_len) = if is_supported(opcode::Accept::CODE) {
+        let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut addr_len = mem::size_of_val(&addr) as libc::socklen_t;
+        let sqe = opcode::Accept::new(types::Fd(fd), &mut addr as *mut _ as *mut _, &mut addr_len)
+            .flags(libc::O_CLOEXEC)
+            .build();
+        let conn = submit(sqe)?.await?;
+        (conn, addr, addr_len)
+    } else {
+        unblock(move || {
+            let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+            let mut addr_len = mem::size_of_val(&addr) as libc::socklen_t;
+            let conn = cvt(unsafe {
+                libc::accept4(
+                    fd,
+                    &mut addr as *mut _ as *mut _,
+                    &mut addr_len,
+                    libc::SOCK_CLOEXEC,
+                )
+            })?;
+            Ok::<_, Error>((conn, addr, addr_len))
+        })
+        .await?
+    };
     unsafe {
         let conn = OwnedFd::from_raw_fd(conn as _);
         let sock_addr = SockAddr::new(addr, addr_len);
@@ -123,16 +783,28 @@ pub(crate) async fn accept(fd: BorrowedFd<'_>) -> Result<(OwnedFd, SockAddr)> {
 
 /// See also `man connect.2`.
 pub(crate) async fn connect(fd: BorrowedFd<'_>, addr: SockAddr) -> Result<()> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Connect::new(fd, addr.as_ptr(), addr.len()).build();
-    submit(sqe)?.await.map(|_| ())
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Connect::CODE) {
+        let sqe = opcode::Connect::new(types::Fd(fd), addr.as_ptr(), addr.len()).build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::connect(fd, addr.as_ptr(), addr.len()) }))
+            .await
+            .map(|_| ())
+    }
 }
 
 /// See also `man shutdown.2`.
 pub(crate) async fn shutdown(fd: BorrowedFd<'_>, how: libc::c_int) -> Result<()> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Shutdown::new(fd, how).build();
-    submit(sqe)?.await.map(|_| ())
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Shutdown::CODE) {
+        let sqe = opcode::Shutdown::new(types::Fd(fd), how).build();
+        submit(sqe)?.await.map(|_| ())
+    } else {
+        unblock(move || cvt(unsafe { libc::shutdown(fd, how) }))
+            .await
+            .map(|_| ())
+    }
 }
 
 /// See also `man read.2`.
@@ -146,11 +818,73 @@ pub(crate) async fn pread<'a>(
     buf: &'a mut [u8],
     pos: libc::off64_t,
 ) -> Result<usize> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Read::new(fd, buf.as_mut_ptr(), buf.len() as _)
-        .offset(pos)
-        .build();
-    submit(sqe)?.await.map(|n| n as _)
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Read::CODE) {
+        let sqe = opcode::Read::new(types::Fd(fd), buf.as_mut_ptr(), buf.len() as _)
+            .offset(pos)
+            .build();
+        submit(sqe)?.await.map(|n| n as _)
+    } else {
+        let len = buf.len();
+        let buf = SendPtr(buf.as_mut_ptr());
+        unblock(move || {
+            let buf = buf.get() as *mut libc::c_void;
+            if pos < 0 {
+                cvt(unsafe { libc::read(fd, buf, len) } as i64)
+            } else {
+                cvt(unsafe { libc::pread64(fd, buf, len, pos) } as i64)
+            }
+        })
+        .await
+        .map(|n| n as _)
+    }
+}
+
+/// Like [`pread`], but polls for the completion if the runtime uses IOPOLL.
+///
+/// The file must be opened with `O_DIRECT`. Falls back to [`pread`] if the
+/// file doesn't support polling.
+pub(crate) async fn pread_direct<'a>(
+    fd: BorrowedFd<'a>,
+    buf: &'a mut [u8],
+    pos: libc::off64_t,
+) -> Result<usize> {
+    if is_supported(opcode::Read::CODE) {
+        let sqe = opcode::Read::new(types::Fd(fd.as_raw_fd()), buf.as_mut_ptr(), buf.len() as _)
+            .offset(pos)
+            .build();
+        if let Some(op) = submit_polled(sqe)? {
+            match op.await {
+                Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => {}
+                res => return res.map(|n| n as _),
+            }
+        }
+    }
+    pread(fd, buf, pos).await
+}
+
+/// Reads from a file in non-blocking mode.
+///
+/// Returns [`ErrorKind::WouldBlock`] if there is nothing to read, so this
+/// never blocks and runs on the current thread.
+///
+/// See also `man read.2`.
+pub(crate) fn try_read(fd: BorrowedFd<'_>, buf: &mut [u8]) -> Result<usize> {
+    let ret = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+    cvt(ret as i64).map(|n| n as usize)
+}
+
+/// Waits for `events` on a file, and returns the events that are ready.
+///
+/// The operation doesn't borrow any memory, so the future can be dropped
+/// before it completes, which cancels the operation.
+///
+/// See also `man poll.2`.
+pub(crate) async fn poll(fd: BorrowedFd<'_>, events: libc::c_short) -> Result<libc::c_short> {
+    let sqe = opcode::PollAdd::new(types::Fd(fd.as_raw_fd()), events as u32).build();
+    // Safety: the entry doesn't point to any memory.
+    let op = unsafe { submit_owned(sqe)? };
+    op.await.map(|events| events as libc::c_short)
 }
 
 /// See also `man write.2`.
@@ -164,13 +898,373 @@ pub(crate) async fn pwrite<'a>(
     buf: &'a [u8],
     pos: libc::off64_t,
 ) -> Result<usize> {
-    let fd = types::Fd(fd.as_raw_fd());
-    let sqe = opcode::Write::new(fd, buf.as_ptr(), buf.len() as _)
-        .offset(pos)
-        .build();
-    submit(sqe)?.await.map(|n| n as _)
+    let fd = fd.as_raw_fd();
+    if is_supported(opcode::Write::CODE) {
+        let sqe = opcode::Write::new(types::Fd(fd), buf.as_ptr(), buf.len() as _)
+            .offset(pos)
+            .build();
+        submit(sqe)?.await.map(|n| n as _)
+    } else {
+        let len = buf.len();
+        let buf = SendPtr(buf.as_ptr() as *mut u8);
+        unblock(move || {
+            let buf = buf.get() as *const libc::c_void;
+            if pos < 0 {
+                cvt(unsafe { libc::write(fd, buf, len) } as i64)
+            } else {
+                cvt(unsafe { libc::pwrite64(fd, buf, len, pos) } as i64)
+            }
+        })
+        .await
+        .map(|n| n as _)
+    }
+}
+
+/// Like [`pwrite`], but polls for the completion if the runtime uses IOPOLL.
+///
+/// The file must be opened with `O_DIRECT`. Falls back to [`pwrite`] if the
+/// file doesn't support polling.
+pub(crate) async fn pwrite_direct<'a>(
+    fd: BorrowedFd<'a>,
+    buf: &'a [u8],
+    pos: libc::off64_t,
+) -> Result<usize> {
+    if is_supported(opcode::Write::CODE) {
+        let sqe = opcode::Write::new(types::Fd(fd.as_raw_fd()), buf.as_ptr(), buf.len() as _)
+            .offset(pos)
+            .build();
+        if let Some(op) = submit_polled(sqe)? {
+            match op.await {
+                Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => {}
+                res => return res.map(|n| n as _),
+            }
+        }
+    }
+    pwrite(fd, buf, pos).await
+}
+
+/// See also `man fchmod.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn fchmod(fd: BorrowedFd<'_>, mode: libc::mode_t) -> Result<()> {
+    let fd = fd.as_raw_fd();
+    unblock(move || cvt(unsafe { libc::fchmod(fd, mode) }))
+        .await
+        .map(|_| ())
+}
+
+/// See also `man copy_file_range.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool.
+pub(crate) async fn copy_file_range(
+    fd_in: BorrowedFd<'_>,
+    fd_out: BorrowedFd<'_>,
+    len: usize,
+) -> Result<usize> {
+    let fd_in = fd_in.as_raw_fd();
+    let fd_out = fd_out.as_raw_fd();
+    unblock(move || {
+        let ret = unsafe {
+            libc::copy_file_range(
+                fd_in,
+                std::ptr::null_mut(),
+                fd_out,
+                std::ptr::null_mut(),
+                len,
+                0,
+            )
+        };
+        cvt(ret as i64)
+    })
+    .await
+    .map(|n| n as _)
+}
+
+/// See also `man getdents64.2`.
+///
+/// io_uring doesn't support this operation, so it always runs on the blocking
+/// pool. The buffer is returned with the number of bytes read into it.
+pub(crate) fn getdents(dir: Arc<OwnedFd>, mut buf: Vec<u8>) -> Blocking<(Vec<u8>, Result<usize>)> {
+    spawn_blocking(move || {
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_getdents64,
+                dir.as_raw_fd(),
+                buf.as_mut_ptr(),
+                buf.len(),
+            )
+        };
+        let res = cvt(ret).map(|n| n as usize);
+        (buf, res)
+    })
+}
+
+/// See also `F_OFD_SETLKW` in `man fcntl.2`.
+///
+/// io_uring doesn't support this operation, and waiting for it on the blocking
+/// pool can't be cancelled. So the lock is tried with `F_OFD_SETLK` until it is
+/// acquired, sleeping with an exponential backoff between attempts.
+pub(crate) async fn ofd_lock(fd: BorrowedFd<'_>, lock: libc::flock) -> Result<()> {
+    const fn millis(ms: u32) -> types::Timespec {
+        types::Timespec::new().nsec(ms * 1_000_000)
+    }
+    // The kernel reads a timeout when it is submitted, which can be after the
+    // sleep is dropped, so the timeouts are static.
+    static BACKOFF: [types::Timespec; 7] = [
+        millis(1),
+        millis(2),
+        millis(4),
+        millis(8),
+        millis(16),
+        millis(32),
+        millis(64),
+    ];
+    let mut step = 0;
+    loop {
+        match try_ofd_lock(fd, lock) {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            res => return res,
+        }
+        if is_supported(opcode::Timeout::CODE) {
+            sleep(&BACKOFF[step]).await?;
+        } else {
+            let backoff = Duration::from_millis(1 << step);
+            spawn_blocking(move || std::thread::sleep(backoff)).await;
+        }
+        step = (step + 1).min(BACKOFF.len() - 1);
+    }
+}
+
+/// Waits until `timeout` elapses.
+///
+/// The operation only points to `timeout`, which is static, so it is
+/// cancelled if the returned future is dropped.
+async fn sleep(timeout: &'static types::Timespec) -> Result<()> {
+    let sqe = opcode::Timeout::new(timeout).build();
+    // Safety: the entry only points to static memory.
+    match unsafe { submit_owned(sqe)? }.await {
+        Err(e) if e.raw_os_error() == Some(libc::ETIME) => Ok(()),
+        res => res.map(drop),
+    }
+}
+
+/// See also `F_OFD_SETLK` in `man fcntl.2`.
+///
+/// This operation never blocks, so it runs on the current thread.
+pub(crate) fn try_ofd_lock(fd: BorrowedFd<'_>, lock: libc::flock) -> Result<()> {
+    cvt(unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_OFD_SETLK, &lock) }).map(drop)
+}
+
+/// See also `man lseek.2`.
+///
+/// This operation doesn't wait for I/O, so it runs on the current thread.
+pub(crate) fn lseek(fd: BorrowedFd<'_>, offset: libc::off64_t, whence: libc::c_int) -> Result<u64> {
+    let ret = unsafe { libc::lseek64(fd.as_raw_fd(), offset, whence) };
+    if ret >= 0 {
+        Ok(ret as u64)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// Returns the file status flags of `fd`.
+///
+/// See also `F_GETFL` in `man fcntl.2`.
+pub(crate) fn get_status_flags(fd: BorrowedFd<'_>) -> Result<libc::c_int> {
+    cvt(unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) }).map(|flags| flags as libc::c_int)
+}
+
+/// See also `man pidfd_open.2`.
+///
+/// This operation never blocks, so it runs on the current thread.
+pub(crate) fn pidfd_open(pid: libc::pid_t) -> Result<OwnedFd> {
+    let fd = cvt(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// See also `man pidfd_send_signal.2`.
+///
+/// This operation never blocks, so it runs on the current thread.
+pub(crate) fn pidfd_send_signal(fd: BorrowedFd<'_>, sig: libc::c_int) -> Result<()> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_pidfd_send_signal,
+            fd.as_raw_fd(),
+            sig,
+            std::ptr::null::<libc::siginfo_t>(),
+            0,
+        )
+    };
+    cvt(ret).map(drop)
+}
+
+/// Waits for the process of a pidfd to exit, and reaps it.
+///
+/// If io_uring doesn't support this operation, the pidfd is polled until the
+/// process exits instead.
+///
+/// See also `P_PIDFD` in `man waitid.2`.
+pub(crate) async fn waitid(fd: BorrowedFd<'_>) -> Result<libc::siginfo_t> {
+    if is_supported(IORING_OP_WAITID) {
+        let mut info = SigInfo(unsafe { mem::zeroed() });
+        let sqe = RawEntry {
+            opcode: IORING_OP_WAITID,
+            fd: fd.as_raw_fd(),
+            addr2: info.as_mut_ptr() as u64,
+            len: libc::P_PIDFD,
+            // This is the `file_index` field for this operation.
+            splice_fd_in: libc::WEXITED,
+            ..Default::default()
+        };
+        submit(sqe.build())?.await?;
+        Ok(info.0)
+    } else {
+        loop {
+            // The pidfd becomes readable when the process exits.
+            poll(fd, libc::POLLIN).await?;
+            if let Some(info) = try_waitid(fd)? {
+                return Ok(info);
+            }
+        }
+    }
+}
+
+/// Reaps the process of a pidfd if it has exited.
+///
+/// This operation never blocks, so it runs on the current thread.
+///
+/// See also `WNOHANG` in `man waitid.2`.
+pub(crate) fn try_waitid(fd: BorrowedFd<'_>) -> Result<Option<libc::siginfo_t>> {
+    let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+    let options = libc::WEXITED | libc::WNOHANG;
+    cvt(unsafe {
+        libc::waitid(
+            libc::P_PIDFD,
+            fd.as_raw_fd() as libc::id_t,
+            &mut info,
+            options,
+        )
+    })?;
+    // The pid is not set if the process hasn't exited yet.
+    Ok((unsafe { info.si_pid() } != 0).then_some(info))
+}
+
+/// Waits for a process to exit, and reaps it.
+///
+/// This is only used if the kernel doesn't support pidfds, so the process
+/// can't be waited for with io_uring, and it runs on the blocking pool.
+///
+/// See also `man waitpid.2`.
+pub(crate) fn waitpid(pid: libc::pid_t) -> Blocking<Result<libc::c_int>> {
+    spawn_blocking(move || loop {
+        let mut status = 0;
+        match cvt(unsafe { libc::waitpid(pid, &mut status, 0) }) {
+            Ok(_) => return Ok(status),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    })
+}
+
+/// Reaps a process if it has exited.
+///
+/// This operation never blocks, so it runs on the current thread.
+///
+/// See also `WNOHANG` in `man waitpid.2`.
+pub(crate) fn try_waitpid(pid: libc::pid_t) -> Result<Option<libc::c_int>> {
+    let mut status = 0;
+    let ret = cvt(unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) })?;
+    Ok((ret != 0).then_some(status))
+}
+
+/// See also `man kill.2`.
+///
+/// This operation never blocks, so it runs on the current thread.
+pub(crate) fn kill(pid: libc::pid_t, sig: libc::c_int) -> Result<()> {
+    cvt(unsafe { libc::kill(pid, sig) }).map(drop)
 }
 
 fn new_path_str(path: &Path) -> Result<CString> {
     CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::from(ErrorKind::InvalidFilename))
 }
+
+fn new_name_str(name: &OsStr) -> Result<CString> {
+    CString::new(name.as_bytes()).map_err(|_| Error::from(ErrorKind::InvalidInput))
+}
+
+/// Converts the return value of a blocking system call to a result.
+fn cvt<T: Into<i64>>(ret: T) -> Result<u32> {
+    let ret = ret.into();
+    if ret >= 0 {
+        Ok(ret as u32)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// A pointer to a buffer borrowed by a blocking operation.
+///
+/// This is safe to send because the future of the operation can not be
+/// dropped before it completes.
+#[derive(Clone, Copy)]
+struct SendPtr(*mut u8);
+
+unsafe impl Send for SendPtr {}
+
+impl SendPtr {
+    fn get(self) -> *mut u8 {
+        self.0
+    }
+}
+
+/// A `siginfo_t` filled in by an operation.
+///
+/// This is safe to send because it only contains plain data.
+struct SigInfo(libc::siginfo_t);
+
+unsafe impl Send for SigInfo {}
+
+impl SigInfo {
+    fn as_mut_ptr(&mut self) -> *mut libc::siginfo_t {
+        &mut self.0
+    }
+}
+
+// io-uring doesn't provide builders for these operations.
+const IORING_OP_FSETXATTR: u8 = 41;
+const IORING_OP_SETXATTR: u8 = 42;
+const IORING_OP_FGETXATTR: u8 = 43;
+const IORING_OP_GETXATTR: u8 = 44;
+const IORING_OP_WAITID: u8 = 50;
+
+/// The layout of a submission entry, for operations that io-uring doesn't
+/// provide builders for.
+///
+/// See `struct io_uring_sqe` in `linux/io_uring.h`.
+#[repr(C)]
+#[derive(Default)]
+struct RawEntry {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    addr2: u64,
+    addr: u64,
+    len: u32,
+    op_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+impl RawEntry {
+    fn build(self) -> squeue::Entry {
+        // Safety: `squeue::Entry` is a `repr(C)` wrapper of the same layout.
+        unsafe { mem::transmute(self) }
+    }
+}
@@ -1,4 +1,14 @@
-use std::{cell::RefCell, collections::VecDeque, future::Future, io::Result, sync::Mutex, thread};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::Future,
+    io::Result,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    sync::{mpsc as std_mpsc, Mutex},
+    task::{Context, Poll},
+    thread,
+};
 
 use futures::channel::mpsc;
 use io_uring::squeue;
@@ -6,14 +16,16 @@ use log::trace;
 use scoped_tls::scoped_thread_local;
 
 use super::{
+    blocking::{Blocking, Unblock},
     driver::{Driver, Op, Unpark},
-    Shared,
+    Builder, Features, Shared,
 };
 use crate::task::{JoinHandle, Schedule, Task};
 
 enum Message {
     Shutdown,
     Schedule(Task),
+    Cancel(Op),
 }
 
 type Sender = mpsc::UnboundedSender<Message>;
@@ -22,6 +34,8 @@ type Receiver = mpsc::UnboundedReceiver<Message>;
 struct Local {
     id: usize,
     shared: Shared,
+    tx: Sender,
+    unpark: Unpark,
     rx: RefCell<Receiver>,
     driver: RefCell<Driver>,
     run_queue: RefCell<VecDeque<Task>>,
@@ -31,19 +45,22 @@ struct Local {
 impl Local {
     fn new(
         id: usize,
-        rx: Receiver,
+        (tx, rx): (Sender, Receiver),
         unpark: Unpark,
         shared: Shared,
-        event_interval: usize,
+        builder: &Builder,
+        attach_wq: Option<RawFd>,
     ) -> Result<Self> {
-        let driver = Driver::new(unpark)?;
+        let driver = Driver::new(unpark.clone(), builder, attach_wq)?;
         Ok(Self {
             id,
             shared,
+            tx,
+            unpark,
             rx: RefCell::new(rx),
             driver: RefCell::new(driver),
             run_queue: RefCell::new(VecDeque::new()),
-            event_interval,
+            event_interval: builder.event_interval,
         })
     }
 
@@ -61,6 +78,10 @@ impl Local {
                         task.poll();
                         num_tasks += 1;
                     }
+                    Message::Cancel(op) => {
+                        self.driver.borrow_mut().cancel(op)?;
+                        num_tasks += 1;
+                    }
                 }
             }
             trace!("worker {} polled {} tasks", self.id, num_tasks);
@@ -113,21 +134,42 @@ impl Worker {
         })
     }
 
+    /// Launches a thread to run this worker.
+    ///
+    /// Returns the file descriptor of the worker's ring.
     pub(super) fn launch(
         &self,
         shared: Shared,
-        stack_size: usize,
-        event_interval: usize,
-    ) -> Result<()> {
-        let rx = self.rx.lock().unwrap().take().unwrap();
-        let local = Local::new(self.id, rx, self.unpark.clone(), shared, event_interval)?;
-        let thread_name = format!("photonio-worker/{}", self.id);
+        builder: &Builder,
+        attach_wq: Option<RawFd>,
+    ) -> Result<RawFd> {
+        let id = self.id;
+        let channel = (self.tx.clone(), self.rx.lock().unwrap().take().unwrap());
+        let unpark = self.unpark.clone();
+        let builder = builder.clone();
+        // The ring is created on the worker thread, since some setup options
+        // (e.g. `IORING_SETUP_SINGLE_ISSUER`) bind the ring to the thread that
+        // creates it.
+        let (init_tx, init_rx) = std_mpsc::sync_channel(1);
+        let thread_name = format!("photonio-worker/{}", id);
         trace!("launch {}", thread_name);
         thread::Builder::new()
             .name(thread_name)
-            .stack_size(stack_size)
-            .spawn(move || enter(local))?;
-        Ok(())
+            .stack_size(builder.thread_stack_size)
+            .spawn(
+                move || match Local::new(id, channel, unpark, shared, &builder, attach_wq) {
+                    Ok(local) => {
+                        let fd = local.driver.borrow().as_raw_fd();
+                        init_tx.send(Ok(fd)).unwrap();
+                        enter(local)
+                    }
+                    Err(e) => {
+                        init_tx.send(Err(e)).unwrap();
+                        Ok(())
+                    }
+                },
+            )?;
+        init_rx.recv().unwrap()
     }
 
     pub(super) fn schedule<F>(&self, id: u64, future: F) -> JoinHandle<F::Output>
@@ -135,7 +177,12 @@ impl Worker {
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        let (task, handle) = Task::new(id, future, Scheduler);
+        let scheduler = Scheduler {
+            id: self.id,
+            tx: self.tx.clone(),
+            unpark: self.unpark.clone(),
+        };
+        let (task, handle) = Task::new(id, future, scheduler);
         self.tx.unbounded_send(Message::Schedule(task)).unwrap();
         self.unpark.unpark().unwrap();
         handle
@@ -144,7 +191,8 @@ impl Worker {
 
 impl Drop for Worker {
     fn drop(&mut self) {
-        self.tx.unbounded_send(Message::Shutdown).unwrap();
+        // The worker might have failed to launch.
+        let _ = self.tx.unbounded_send(Message::Shutdown);
     }
 }
 
@@ -170,13 +218,128 @@ pub(super) fn submit(op: squeue::Entry) -> Result<Op> {
     })
 }
 
-struct Scheduler;
+/// Submits a read or write of a file opened with `O_DIRECT` to be polled for
+/// completion.
+///
+/// Returns `None` if the runtime doesn't use IOPOLL.
+pub(super) fn submit_polled(op: squeue::Entry) -> Result<Option<Op>> {
+    CURRENT.with(|local| {
+        let mut driver = local.driver.borrow_mut();
+        unsafe { driver.add_polled(op) }
+    })
+}
+
+/// Submits an operation that doesn't borrow any memory.
+///
+/// # Safety
+///
+/// The submission entry must not point to any memory.
+pub(super) unsafe fn submit_owned(op: squeue::Entry) -> Result<OwnedOp> {
+    CURRENT.with(|local| {
+        let mut op = local.driver.borrow_mut().add(op)?;
+        op.set_owned();
+        Ok(OwnedOp {
+            op: Some(op),
+            tx: local.tx.clone(),
+            unpark: local.unpark.clone(),
+        })
+    })
+}
+
+/// An operation that doesn't borrow any memory.
+///
+/// Unlike [`Op`], this can be dropped before it completes, which cancels the
+/// operation on the worker that submitted it.
+pub(super) struct OwnedOp {
+    op: Option<Op>,
+    tx: Sender,
+    unpark: Unpark,
+}
+
+impl Future for OwnedOp {
+    type Output = Result<u32>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let op = self.op.as_mut().unwrap();
+        Pin::new(op).poll(cx)
+    }
+}
+
+impl Drop for OwnedOp {
+    fn drop(&mut self) {
+        let op = self.op.take().unwrap();
+        if !op.is_pending() {
+            return;
+        }
+        let is_local = CURRENT.is_set() && CURRENT.with(|local| local.tx.same_receiver(&self.tx));
+        if is_local {
+            CURRENT.with(|local| {
+                let _ = local.driver.borrow_mut().cancel(op);
+            });
+        } else if self.tx.unbounded_send(Message::Cancel(op)).is_ok() {
+            // Otherwise, the worker has been shut down, and its ring with it.
+            let _ = self.unpark.unpark();
+        }
+    }
+}
+
+pub(super) fn is_supported(opcode: u8) -> bool {
+    CURRENT.with(|local| local.driver.borrow().features().is_supported(opcode))
+}
+
+pub(super) fn spawn_blocking<F, R>(f: F) -> Blocking<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    CURRENT.with(|local| local.shared.spawn_blocking(f))
+}
+
+pub(super) fn unblock<F, R>(f: F) -> Unblock<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    CURRENT.with(|local| local.shared.unblock(f))
+}
+
+/// Returns the io_uring features supported by the kernel.
+///
+/// # Panics
+///
+/// Panics if called outside a runtime.
+pub fn features() -> Features {
+    CURRENT.with(|local| local.driver.borrow().features().clone())
+}
+
+struct Scheduler {
+    id: usize,
+    tx: Sender,
+    unpark: Unpark,
+}
 
 impl Schedule for Scheduler {
     fn schedule(&self, task: Task) {
-        CURRENT.with(|local| {
-            let mut run_queue = local.run_queue.borrow_mut();
-            run_queue.push_back(task);
-        })
+        if CURRENT.is_set() {
+            CURRENT.with(|local| {
+                if local.id == self.id {
+                    let mut run_queue = local.run_queue.borrow_mut();
+                    run_queue.push_back(task);
+                } else {
+                    send_task(&self.tx, &self.unpark, task);
+                }
+            })
+        } else {
+            // The task is woken up by a thread outside the runtime (e.g. the
+            // blocking pool).
+            send_task(&self.tx, &self.unpark, task);
+        }
+    }
+}
+
+fn send_task(tx: &Sender, unpark: &Unpark, task: Task) {
+    // The worker might have been shut down.
+    if tx.unbounded_send(Message::Schedule(task)).is_ok() {
+        unpark.unpark().unwrap();
     }
 }
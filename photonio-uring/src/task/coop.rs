@@ -0,0 +1,168 @@
+//! Cooperative scheduling.
+//!
+//! Each task gets a budget every time it is polled by a worker. Futures
+//! provided by this crate consume one unit of the budget when they make
+//! progress, and return [`Poll::Pending`] once the budget is exhausted, so
+//! that a task that keeps making progress yields back to the runtime
+//! eventually.
+
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The number of operations a task can perform before it is forced to yield.
+const INITIAL_BUDGET: u8 = 128;
+
+#[derive(Copy, Clone)]
+struct Budget(Option<u8>);
+
+impl Budget {
+    const fn initial() -> Self {
+        Self(Some(INITIAL_BUDGET))
+    }
+
+    const fn unconstrained() -> Self {
+        Self(None)
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Budget> = const { Cell::new(Budget::unconstrained()) };
+}
+
+/// Runs `f` with a fresh budget.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    with_budget(Budget::initial(), f)
+}
+
+fn with_budget<R>(budget: Budget, f: impl FnOnce() -> R) -> R {
+    struct ResetGuard(Budget);
+
+    impl Drop for ResetGuard {
+        fn drop(&mut self) {
+            CURRENT.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let prev = CURRENT.with(|cell| cell.replace(budget));
+    let _guard = ResetGuard(prev);
+    f()
+}
+
+/// Consumes a unit of the current budget.
+///
+/// Returns [`Poll::Pending`] and wakes the task if the budget is exhausted.
+/// The consumed unit is returned to the budget if the returned guard is
+/// dropped without calling [`RestoreOnPending::made_progress`].
+pub(crate) fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    CURRENT.with(|cell| {
+        let budget = cell.get();
+        match budget.0 {
+            Some(0) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Some(n) => {
+                cell.set(Budget(Some(n - 1)));
+                Poll::Ready(RestoreOnPending(Cell::new(budget)))
+            }
+            None => Poll::Ready(RestoreOnPending(Cell::new(budget))),
+        }
+    })
+}
+
+pub(crate) struct RestoreOnPending(Cell<Budget>);
+
+impl RestoreOnPending {
+    pub(crate) fn made_progress(&self) {
+        self.0.set(Budget::unconstrained());
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        let budget = self.0.get();
+        if budget.0.is_some() {
+            CURRENT.with(|cell| cell.set(budget));
+        }
+    }
+}
+
+/// A future returned by [`unconstrained`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Unconstrained<F> {
+    inner: F,
+}
+
+impl<F: Future> Future for Unconstrained<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+        with_budget(Budget::unconstrained(), || inner.poll(cx))
+    }
+}
+
+/// Turns off cooperative scheduling for a future.
+///
+/// The future will never be forced to yield by the runtime, which might starve
+/// other tasks on the same worker if it keeps making progress.
+pub fn unconstrained<F: Future>(inner: F) -> Unconstrained<F> {
+    Unconstrained { inner }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::task::noop_waker_ref;
+
+    use super::*;
+
+    fn poll_proceed_now() -> Poll<()> {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        poll_proceed(&mut cx).map(|coop| coop.made_progress())
+    }
+
+    #[test]
+    fn budget_exhausted() {
+        budget(|| {
+            for _ in 0..INITIAL_BUDGET {
+                assert!(poll_proceed_now().is_ready());
+            }
+            assert!(poll_proceed_now().is_pending());
+            assert!(poll_proceed_now().is_pending());
+        });
+        // The task gets a new budget when it is polled again.
+        budget(|| assert!(poll_proceed_now().is_ready()));
+    }
+
+    #[test]
+    fn budget_restored_on_pending() {
+        budget(|| {
+            let mut cx = Context::from_waker(noop_waker_ref());
+            for _ in 0..usize::from(INITIAL_BUDGET) * 2 {
+                // No progress is made, so the unit is returned.
+                let Poll::Ready(coop) = poll_proceed(&mut cx) else {
+                    panic!("the budget is exhausted");
+                };
+                drop(coop);
+            }
+        });
+    }
+
+    #[test]
+    fn unconstrained_budget() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        budget(|| {
+            let mut future = Box::pin(unconstrained(futures::future::poll_fn(|_| {
+                for _ in 0..usize::from(INITIAL_BUDGET) * 2 {
+                    assert!(poll_proceed_now().is_ready());
+                }
+                Poll::Ready(())
+            })));
+            assert!(future.as_mut().poll(&mut cx).is_ready());
+        });
+    }
+}
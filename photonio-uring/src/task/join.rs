@@ -2,10 +2,10 @@ use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
 };
 
-use super::{Result, Task};
+use super::{coop, Result, Task};
 
 /// A handle to await a task.
 ///
@@ -42,6 +42,10 @@ impl<T> Future for JoinHandle<T> {
     type Output = Result<T>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.task.join(cx.waker())
+        let coop = ready!(coop::poll_proceed(cx));
+        self.task.join(cx.waker()).map(|v| {
+            coop.made_progress();
+            v
+        })
     }
 }
@@ -0,0 +1,252 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+/// Declares task-local keys of type [`LocalKey`].
+///
+/// # Examples
+///
+/// ```
+/// photonio_uring::task_local! {
+///     pub static REQUEST_ID: u64;
+///     static TENANT: String;
+/// }
+/// ```
+///
+/// [`LocalKey`]: crate::task::LocalKey
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+            $crate::task::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// A key for task-local data.
+///
+/// The value of a key is owned by the future passed to [`Self::scope`], and
+/// it is only set while that future is being polled. So the value follows the
+/// future across awaits, and across threads if the task moves to another
+/// worker.
+///
+/// This type is similar to [`std::thread::LocalKey`], but for asynchronous
+/// tasks instead of threads.
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Sets `value` as the value of this key while `future` is being polled.
+    ///
+    /// The value is only visible to `future` itself. Tasks spawned from it
+    /// don't inherit the value, and need a scope of their own.
+    ///
+    /// The value is dropped when the returned future completes.
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            local: self,
+            slot: Some(value),
+            future: Some(future),
+        }
+    }
+
+    /// Sets `value` as the value of this key while `f` is being called.
+    pub fn sync_scope<F, R>(&'static self, value: T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let mut slot = Some(value);
+        self.scope_inner(&mut slot, f)
+    }
+
+    /// Accesses the value of this key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not set.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.try_with(f)
+            .expect("cannot access a task-local value without setting it first")
+    }
+
+    /// Accesses the value of this key.
+    ///
+    /// Returns an error if the value is not set.
+    pub fn try_with<F, R>(&'static self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner
+            .try_with(|v| v.borrow().as_ref().map(f))
+            .ok()
+            .flatten()
+            .ok_or(AccessError(()))
+    }
+}
+
+impl<T: Clone + 'static> LocalKey<T> {
+    /// Returns a copy of the value of this key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not set.
+    pub fn get(&'static self) -> T {
+        self.with(|v| v.clone())
+    }
+}
+
+impl<T: 'static> LocalKey<T> {
+    fn scope_inner<F, R>(&'static self, slot: &mut Option<T>, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.try_scope_inner(slot, f)
+            .expect("cannot enter a task-local scope while the value is borrowed")
+    }
+
+    /// Like `scope_inner`, but returns `None` without calling `f` if the value
+    /// is borrowed or the thread-local storage is destroyed.
+    fn try_scope_inner<F, R>(&'static self, slot: &mut Option<T>, f: F) -> Option<R>
+    where
+        F: FnOnce() -> R,
+    {
+        struct Guard<'a, T: 'static> {
+            local: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<'a, T: 'static> Drop for Guard<'a, T> {
+            fn drop(&mut self) {
+                // Move the value back to the slot, even if `f` panics.
+                self.local
+                    .inner
+                    .with(|inner| mem::swap(self.slot, &mut *inner.borrow_mut()));
+            }
+        }
+
+        let entered = self.inner.try_with(|inner| {
+            let Ok(mut inner) = inner.try_borrow_mut() else {
+                return false;
+            };
+            mem::swap(slot, &mut *inner);
+            true
+        });
+        if entered != Ok(true) {
+            return None;
+        }
+        let _guard = Guard { local: self, slot };
+        Some(f())
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("LocalKey { .. }")
+    }
+}
+
+/// A future that sets the value of a task-local key while it is being polled.
+///
+/// This future is created by [`LocalKey::scope`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TaskLocalFuture<T: 'static, F> {
+    local: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: Option<F>,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: `future` is never moved out of this pinned struct.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = &mut this.future;
+        this.local.scope_inner(&mut this.slot, || {
+            let fut = future
+                .as_mut()
+                .expect("`TaskLocalFuture` polled after completion");
+            let poll = unsafe { Pin::new_unchecked(fut) }.poll(cx);
+            if poll.is_ready() {
+                *future = None;
+            }
+            poll
+        })
+    }
+}
+
+impl<T: 'static, F> Drop for TaskLocalFuture<T, F> {
+    fn drop(&mut self) {
+        // Drop the future with the value set, since its destructor might access
+        // the value too.
+        if mem::needs_drop::<F>() && self.future.is_some() {
+            let future = &mut self.future;
+            if self
+                .local
+                .try_scope_inner(&mut self.slot, || *future = None)
+                .is_none()
+            {
+                // The value can't be set, so drop the future without it.
+                self.future = None;
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug + 'static, F> fmt::Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskLocalFuture")
+            .field("value", &self.slot)
+            .finish()
+    }
+}
+
+/// An error returned by [`LocalKey::try_with`].
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct AccessError(());
+
+impl fmt::Debug for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessError").finish()
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
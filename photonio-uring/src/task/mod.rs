@@ -22,6 +22,12 @@ pub use join::JoinHandle;
 mod yield_now;
 pub use yield_now::yield_now;
 
+mod local;
+pub use local::{AccessError, LocalKey, TaskLocalFuture};
+
+pub(crate) mod coop;
+pub use coop::{unconstrained, Unconstrained};
+
 /// A unique identifier for a task.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct TaskId(u64);
@@ -58,7 +64,7 @@ impl Task {
     }
 
     pub(crate) fn poll(&self) {
-        unsafe { self.0.poll(&self.0) }
+        coop::budget(|| unsafe { self.0.poll(&self.0) })
     }
 
     pub(super) fn join<T>(&self, waker: &Waker) -> Poll<Result<T>> {
@@ -0,0 +1,208 @@
+//! Measures the throughput of submitting and completing operations.
+//!
+//! Each task reads from `/dev/zero` in a loop, so the cost of an operation is
+//! dominated by the runtime instead of the device.
+//!
+//! The bookkeeping of operations is also measured on its own, comparing the
+//! state kept in each operation with the locked table it replaced, which is
+//! kept in [`baseline`].
+//!
+//! Run with `cargo bench --bench op`.
+
+use std::{
+    sync::Arc,
+    task::Context,
+    time::{Duration, Instant},
+};
+
+use futures::task::noop_waker_ref;
+use photonio::{fs::File, io::ReadAt, task};
+
+const NUM_TASKS: usize = 64;
+const NUM_OPS_PER_TASK: usize = 100_000;
+
+/// The shared table of operations, as used before each operation kept its own
+/// state.
+mod baseline {
+    use std::{
+        io::Result,
+        sync::{Arc, Mutex},
+        task::{Poll, Waker},
+    };
+
+    use slab::Slab;
+
+    #[derive(Default)]
+    enum OpState {
+        #[default]
+        Init,
+        Polled(Waker),
+        Completed(Result<u32>),
+    }
+
+    #[derive(Clone, Default)]
+    pub struct OpTable(Arc<Mutex<Slab<OpState>>>);
+
+    impl OpTable {
+        pub fn add(&mut self) -> usize {
+            let mut table = self.0.lock().unwrap();
+            table.insert(OpState::default())
+        }
+
+        pub fn poll(&mut self, index: usize, waker: &Waker) -> Poll<Result<u32>> {
+            let mut table = self.0.lock().unwrap();
+            let state = table.get_mut(index).unwrap();
+            match std::mem::take(state) {
+                OpState::Init => {
+                    *state = OpState::Polled(waker.clone());
+                    Poll::Pending
+                }
+                OpState::Polled(w) => {
+                    if w.will_wake(waker) {
+                        *state = OpState::Polled(w);
+                    } else {
+                        *state = OpState::Polled(waker.clone());
+                    }
+                    Poll::Pending
+                }
+                OpState::Completed(result) => {
+                    table.remove(index);
+                    Poll::Ready(result)
+                }
+            }
+        }
+
+        pub fn complete(&mut self, index: usize, result: Result<u32>) {
+            let mut table = self.0.lock().unwrap();
+            let state = table.get_mut(index).unwrap();
+            match std::mem::take(state) {
+                OpState::Init => {
+                    *state = OpState::Completed(result);
+                }
+                OpState::Polled(w) => {
+                    *state = OpState::Completed(result);
+                    w.wake();
+                }
+                OpState::Completed(..) => unreachable!(),
+            }
+        }
+    }
+}
+
+/// The state kept in each operation, as in `photonio-uring`.
+mod current {
+    use std::{
+        sync::atomic::{AtomicU64, Ordering},
+        task::{Context, Poll},
+    };
+
+    use futures::task::AtomicWaker;
+
+    pub struct OpState {
+        result: AtomicU64,
+        waker: AtomicWaker,
+    }
+
+    impl OpState {
+        const PENDING: u64 = u64::MAX;
+
+        pub fn new() -> Self {
+            Self {
+                result: AtomicU64::new(Self::PENDING),
+                waker: AtomicWaker::new(),
+            }
+        }
+
+        pub fn complete(&self, result: i32) {
+            self.result.store(result as u32 as u64, Ordering::Release);
+            self.waker.wake();
+        }
+
+        pub fn poll(&self, cx: &mut Context<'_>) -> Poll<i32> {
+            let result = self.result.load(Ordering::Acquire);
+            if result != Self::PENDING {
+                return Poll::Ready(result as u32 as i32);
+            }
+            self.waker.register(cx.waker());
+            let result = self.result.load(Ordering::Acquire);
+            if result != Self::PENDING {
+                Poll::Ready(result as u32 as i32)
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs the bookkeeping of `NUM_TASKS * NUM_OPS_PER_TASK` operations, with
+/// `NUM_TASKS` of them in flight at a time.
+fn bookkeeping(name: &str, mut op: impl FnMut(&mut Context<'_>)) {
+    let mut cx = Context::from_waker(noop_waker_ref());
+    let start = Instant::now();
+    for _ in 0..NUM_TASKS * NUM_OPS_PER_TASK {
+        op(&mut cx);
+    }
+    report(name, start.elapsed());
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let num_ops = NUM_TASKS * NUM_OPS_PER_TASK;
+    println!(
+        "{}: {} ops in {:?}, {:.0} ops/s",
+        name,
+        num_ops,
+        elapsed,
+        num_ops as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[photonio::main(num_threads = 1)]
+async fn main() {
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(NUM_TASKS);
+    for _ in 0..NUM_TASKS {
+        handles.push(task::spawn(async {
+            let file = File::open("/dev/zero").await.unwrap();
+            let mut buf = [0; 8];
+            for _ in 0..NUM_OPS_PER_TASK {
+                file.read_at(&mut buf, 0).await.unwrap();
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    report("runtime", start.elapsed());
+
+    // Each operation is added, polled before it completes, completed and
+    // polled again, as the runtime does.
+    let mut table = baseline::OpTable::default();
+    let mut inflight = Vec::with_capacity(NUM_TASKS);
+    bookkeeping("baseline table", |cx| {
+        let index = table.add();
+        assert!(table.poll(index, cx.waker()).is_pending());
+        inflight.push(index);
+        if inflight.len() == NUM_TASKS {
+            for index in inflight.drain(..) {
+                table.complete(index, Ok(8));
+                assert!(table.poll(index, cx.waker()).is_ready());
+            }
+        }
+    });
+
+    let mut inflight = Vec::with_capacity(NUM_TASKS);
+    bookkeeping("op state", |cx| {
+        let state = Arc::new(current::OpState::new());
+        // The driver holds a reference while the operation is in flight.
+        let token = Arc::into_raw(state.clone());
+        assert!(state.poll(cx).is_pending());
+        inflight.push((state, token));
+        if inflight.len() == NUM_TASKS {
+            for (state, token) in inflight.drain(..) {
+                // Safety: the token is created by `Arc::into_raw` above.
+                unsafe { Arc::from_raw(token) }.complete(8);
+                assert!(state.poll(cx).is_ready());
+            }
+        }
+    });
+}
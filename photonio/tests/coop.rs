@@ -0,0 +1,63 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use photonio::{
+    fs::{self, File},
+    io::{ReadAt, WriteAt},
+    task,
+};
+
+#[photonio::test(num_threads = 1)]
+async fn unconstrained() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+
+    let file = File::create(&path).await.unwrap();
+    let n = task::unconstrained(async {
+        let mut n = 0;
+        for i in 0..1024 {
+            n += file.write_at(b"x", i).await.unwrap();
+        }
+        n
+    })
+    .await;
+    assert_eq!(n, 1024);
+
+    let handle = task::spawn(async move {
+        let file = File::open(path).await.unwrap();
+        let mut buf = [0; 1];
+        let mut n = 0;
+        for i in 0..1024 {
+            n += file.read_at(&mut buf, i).await.unwrap();
+        }
+        n
+    });
+    assert_eq!(task::unconstrained(handle).await.unwrap(), 1024);
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test(num_threads = 1)]
+async fn starvation() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+    fs::write(&path, "x").await.unwrap();
+
+    // The spawned task shares the only worker with this task, which loops on
+    // reads that complete right away.
+    let done = Arc::new(AtomicBool::new(false));
+    let handle = task::spawn({
+        let done = done.clone();
+        async move { done.store(true, Ordering::Release) }
+    });
+    let file = File::open(&path).await.unwrap();
+    let mut buf = [0; 1];
+    while !done.load(Ordering::Acquire) {
+        assert_eq!(file.read_at(&mut buf, 0).await.unwrap(), 1);
+    }
+    handle.await.unwrap();
+
+    dir.close().await.unwrap();
+}
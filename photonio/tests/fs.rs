@@ -1,26 +1,809 @@
+use std::{io::ErrorKind, path::Path, time::Duration};
+
+use futures::TryStreamExt;
 use photonio::{
-    fs::{File, OpenOptions},
-    io::{Read, ReadAt, Write, WriteAt},
+    fs::{self, File, OpenOptions},
+    io::{
+        AlignedBuf, Read, ReadAt, ReadAtExt, ReadExt, Seek, SeekFrom, Write, WriteAt, WriteAtExt,
+        WriteExt,
+    },
+    task,
 };
 
 #[photonio::test(env_logger = true)]
 async fn file() {
-    let path = "/tmp/test.txt";
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
 
-    let mut file = File::create(path).await.unwrap();
+    let mut file = File::create(&path).await.unwrap();
     file.write(b"hello").await.unwrap();
     file.write_at(b"world", 5).await.unwrap();
 
     let mut buf = [0; 10];
-    let mut file = File::open(path).await.unwrap();
+    let mut file = File::open(&path).await.unwrap();
     file.read(&mut buf[..5]).await.unwrap();
     file.read_at(&mut buf[5..], 5).await.unwrap();
     assert_eq!(&buf, b"helloworld");
 
-    let file = OpenOptions::new().write(true).open(path).await.unwrap();
+    let file = OpenOptions::new().write(true).open(&path).await.unwrap();
     let meta = file.metadata().await.unwrap();
     assert_eq!(meta.len(), 10);
     file.set_len(5).await.unwrap();
     let meta = file.metadata().await.unwrap();
     assert_eq!(meta.len(), 5);
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn try_clone() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+
+    let file = File::create(&path).await.unwrap();
+    let clone = file.try_clone().await.unwrap();
+    file.write_all_at(b"hello", 0).await.unwrap();
+    clone.write_all_at(b"world", 5).await.unwrap();
+    drop(file);
+    drop(clone);
+    assert_eq!(fs::read(&path).await.unwrap(), b"helloworld");
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn seek() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+    fs::write(&path, "hello world").await.unwrap();
+
+    let mut file = File::open(&path).await.unwrap();
+    let mut buf = [0; 5];
+    file.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello");
+    assert_eq!(file.seek(SeekFrom::Current(1)).await.unwrap(), 6);
+    file.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"world");
+    assert_eq!(file.seek(SeekFrom::End(-5)).await.unwrap(), 6);
+    assert_eq!(file.seek(SeekFrom::Start(0)).await.unwrap(), 0);
+    let err = file.seek(SeekFrom::Current(-1)).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // Clones share the cursor.
+    let mut clone = file.try_clone().await.unwrap();
+    clone.seek(SeekFrom::Start(6)).await.unwrap();
+    file.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"world");
+    assert_eq!(clone.seek(SeekFrom::Current(0)).await.unwrap(), 11);
+
+    let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+    file.write_all(b"!").await.unwrap();
+    assert_eq!(file.seek(SeekFrom::Current(0)).await.unwrap(), 12);
+    assert_eq!(fs::read(&path).await.unwrap(), b"hello world!");
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn read_dir() {
+    let tmp = fs::TempDir::new().await.unwrap();
+    let path = tmp.path();
+
+    fs::create_dir(path.join("dir")).await.unwrap();
+    let mut file = File::create(path.join("file")).await.unwrap();
+    file.write(b"hello").await.unwrap();
+
+    let mut entries: Vec<_> = fs::read_dir(path)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    entries.sort_by_key(|e| e.file_name());
+    assert_eq!(entries.len(), 2);
+
+    let dir = &entries[0];
+    assert_eq!(dir.file_name(), "dir");
+    assert_eq!(dir.path(), path.join("dir"));
+    assert!(dir.file_type().await.unwrap().is_dir());
+    assert!(dir.metadata().await.unwrap().is_dir());
+
+    let file = &entries[1];
+    assert_eq!(file.file_name(), "file");
+    assert!(file.file_type().await.unwrap().is_file());
+    assert_eq!(file.metadata().await.unwrap().len(), 5);
+
+    let mut dir = fs::read_dir(path.join("dir")).await.unwrap();
+    assert!(dir.next_entry().await.unwrap().is_none());
+
+    tmp.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn dir_all() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("root");
+    let outside = dir.path().join("outside");
+    fs::create_dir(&outside).await.unwrap();
+
+    let nested = path.join("a/b/c");
+    fs::create_dir_all(&nested).await.unwrap();
+    fs::create_dir_all(&nested).await.unwrap();
+    assert!(nested.is_dir());
+
+    let file = path.join("a/file");
+    File::create(&file).await.unwrap();
+    std::os::unix::fs::symlink(&outside, path.join("a/b/link")).unwrap();
+    assert!(fs::create_dir_all(file.join("x")).await.is_err());
+
+    fs::remove_dir_all(&path).await.unwrap();
+    assert!(!path.exists());
+    assert!(outside.is_dir());
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn copy() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let from = dir.path().join("from.txt");
+    let to = dir.path().join("to.txt");
+
+    let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+    let file = File::create(&from).await.unwrap();
+    file.write_at(&data, 0).await.unwrap();
+
+    assert_eq!(fs::copy(&from, &to).await.unwrap(), data.len() as u64);
+    assert_eq!(std::fs::read(&to).unwrap(), data);
+    assert!(fs::copy(dir.path(), &to).await.is_err());
+
+    fs::write(&from, "").await.unwrap();
+    assert_eq!(fs::copy(&from, &to).await.unwrap(), 0);
+    assert!(std::fs::read(&to).unwrap().is_empty());
+
+    // Files in procfs report a size of zero.
+    let n = fs::copy("/proc/self/status", &to).await.unwrap();
+    assert!(n > 0);
+    assert!(std::fs::read_to_string(&to).unwrap().contains("Name:"));
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn metadata() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+    let link = dir.path().join("test.link");
+
+    let start = std::time::SystemTime::now() - std::time::Duration::from_secs(1);
+    let mut file = File::create(&path).await.unwrap();
+    file.write(b"hello").await.unwrap();
+    std::os::unix::fs::symlink(&path, &link).unwrap();
+
+    let meta = fs::metadata(&link).await.unwrap();
+    assert!(meta.file_type().is_file());
+    assert_eq!(meta.len(), 5);
+    assert!(meta.modified().unwrap() >= start);
+    assert!(meta.accessed().unwrap() >= start);
+    if let Ok(created) = meta.created() {
+        assert!(created >= start);
+    }
+    let std_meta = std::fs::metadata(&path).unwrap();
+    assert_eq!(meta.permissions(), std_meta.permissions());
+    assert_eq!(meta.modified().unwrap(), std_meta.modified().unwrap());
+
+    let meta = fs::symlink_metadata(&link).await.unwrap();
+    assert!(meta.file_type().is_symlink());
+    assert!(meta.is_symlink());
+
+    assert!(fs::metadata(dir.path().join("none")).await.is_err());
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn links() {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path();
+
+    let original = path.join("original");
+    let hard = path.join("hard");
+    let soft = path.join("soft");
+    let file = File::create(&original).await.unwrap();
+
+    fs::hard_link(&original, &hard).await.unwrap();
+    assert_eq!(fs::metadata(&original).await.unwrap().nlink(), 2);
+    fs::symlink("original", &soft).await.unwrap();
+    assert_eq!(
+        fs::read_link(&soft).await.unwrap(),
+        std::path::Path::new("original")
+    );
+    let name = path.file_name().unwrap();
+    assert_eq!(
+        fs::canonicalize(path.join("..").join(name).join("soft"))
+            .await
+            .unwrap(),
+        std::fs::canonicalize(&original).unwrap()
+    );
+    assert!(fs::read_link(&original).await.is_err());
+
+    fs::set_permissions(&original, std::fs::Permissions::from_mode(0o600))
+        .await
+        .unwrap();
+    let meta = fs::metadata(&original).await.unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+    file.set_permissions(std::fs::Permissions::from_mode(0o640))
+        .await
+        .unwrap();
+    let meta = fs::metadata(&original).await.unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+
+    fs::chown(&soft, Some(meta.uid()), None).await.unwrap();
+    fs::lchown(&soft, None, Some(meta.gid())).await.unwrap();
+    file.chown(Some(meta.uid()), Some(meta.gid()))
+        .await
+        .unwrap();
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn read_write() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = dir.path().join("test.txt");
+
+    fs::write(&path, "hello").await.unwrap();
+    assert_eq!(fs::read(&path).await.unwrap(), b"hello");
+    assert_eq!(fs::read_to_string(&path).await.unwrap(), "hello");
+
+    let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+    fs::write(&path, &data).await.unwrap();
+    assert_eq!(fs::read(&path).await.unwrap(), data);
+    assert!(fs::read_to_string(&path).await.is_err());
+
+    fs::write(&path, b"").await.unwrap();
+    assert!(fs::read(&path).await.unwrap().is_empty());
+
+    // Files in procfs report a size of zero.
+    let status = fs::read_to_string("/proc/self/status").await.unwrap();
+    assert!(status.contains("Name:"));
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn write_atomic() {
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    use photonio::io::WriteExt;
+
+    let dir = fs::TempDir::new().await.unwrap();
+    let target = dir.path().join("target");
+
+    fs::write_atomic(&target, "hello").await.unwrap();
+    assert_eq!(fs::read_to_string(&target).await.unwrap(), "hello");
+    fs::write_atomic(&target, "world").await.unwrap();
+    assert_eq!(fs::read_to_string(&target).await.unwrap(), "world");
+
+    let mut file = fs::AtomicFile::create(&target).await.unwrap();
+    file.write_all(b"hello ").await.unwrap();
+    file.write_at(b"world", 6).await.unwrap();
+    assert_eq!(fs::read_to_string(&target).await.unwrap(), "world");
+    file.commit().await.unwrap();
+    assert_eq!(fs::read_to_string(&target).await.unwrap(), "hello world");
+
+    // Dropping without committing leaves the target untouched.
+    let mut file = fs::AtomicFile::create(&target).await.unwrap();
+    file.write_all(b"dropped").await.unwrap();
+    drop(file);
+    assert_eq!(fs::read_to_string(&target).await.unwrap(), "hello world");
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // The permissions of the target are kept.
+    fs::set_permissions(&target, Permissions::from_mode(0o640))
+        .await
+        .unwrap();
+    fs::write_atomic(&target, "mode").await.unwrap();
+    let meta = fs::metadata(&target).await.unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn dir() {
+    let tmp = fs::TempDir::new().await.unwrap();
+    let path = tmp.path().join("dir");
+    let moved = tmp.path().join("moved");
+    fs::create_dir(&path).await.unwrap();
+
+    let dir = fs::Dir::open(&path).await.unwrap();
+    assert!(fs::Dir::open("/proc/self/status").await.is_err());
+    // Renaming the directory doesn't affect the handle.
+    fs::rename(&path, &moved).await.unwrap();
+    let root = &moved;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true);
+    let mut file = dir.open_file("file", &options).await.unwrap();
+    file.write(b"hello").await.unwrap();
+    assert_eq!(dir.metadata("file").await.unwrap().len(), 5);
+    assert!(dir.metadata(".").await.unwrap().is_dir());
+
+    dir.create_dir("sub").await.unwrap();
+    let sub = dir.open_dir("sub").await.unwrap();
+    dir.rename_to("file", &sub, "renamed").await.unwrap();
+    assert!(dir.metadata("file").await.is_err());
+    assert_eq!(sub.metadata("renamed").await.unwrap().len(), 5);
+
+    let entries: Vec<_> = dir.read_dir().await.unwrap().try_collect().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.file_name(), "sub");
+    // The entries don't know where the directory is now, so their paths are
+    // relative to it.
+    assert_eq!(entry.path(), Path::new("sub"));
+    assert!(entry.file_type().await.unwrap().is_dir());
+    assert!(entry.metadata().await.unwrap().is_dir());
+
+    let names: Vec<_> = sub
+        .read_dir()
+        .await
+        .unwrap()
+        .map_ok(|e| e.file_name())
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(names, ["renamed"]);
+
+    sub.remove_file("renamed").await.unwrap();
+    dir.remove_dir("sub").await.unwrap();
+    dir.sync_all().await.unwrap();
+    assert_eq!(std::fs::read_dir(root).unwrap().count(), 0);
+
+    tmp.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn rename_flags() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let a = &dir.path().join("a");
+    let b = &dir.path().join("b");
+    let c = &dir.path().join("c");
+    fs::write(a, "a").await.unwrap();
+    fs::write(b, "b").await.unwrap();
+
+    let err = fs::rename_noreplace(a, b).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(fs::read_to_string(b).await.unwrap(), "b");
+    fs::rename_noreplace(a, c).await.unwrap();
+    assert!(fs::metadata(a).await.is_err());
+    assert_eq!(fs::read_to_string(c).await.unwrap(), "a");
+
+    let d = &dir.path().join("d");
+    let e = &dir.path().join("e");
+    fs::create_dir(d).await.unwrap();
+    fs::rename_noreplace(d, e).await.unwrap();
+    assert!(fs::metadata(e).await.unwrap().is_dir());
+    let err = fs::rename_noreplace(e, c).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+    match fs::exchange(b, c).await {
+        Ok(()) => {
+            assert_eq!(fs::read_to_string(b).await.unwrap(), "a");
+            assert_eq!(fs::read_to_string(c).await.unwrap(), "b");
+        }
+        Err(e) => assert_eq!(e.kind(), ErrorKind::Unsupported),
+    }
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn space() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    let block = 4096;
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .await
+        .unwrap();
+    file.allocate(0, 4 * block).await.unwrap();
+    let meta = file.metadata().await.unwrap();
+    assert_eq!(meta.len(), 4 * block);
+    assert!(meta.blocks() * 512 >= 4 * block);
+
+    let data: Vec<u8> = (0..4 * block).map(|i| (i / block + 1) as u8).collect();
+    file.write_at(&data, 0).await.unwrap();
+    file.sync_range(
+        0,
+        0,
+        fs::SyncRangeFlags::WRITE | fs::SyncRangeFlags::WAIT_AFTER,
+    )
+    .await
+    .unwrap();
+
+    // Not every filesystem supports these operations.
+    if is_supported(file.punch_hole(0, block).await)
+        && is_supported(file.zero_range(block, block).await)
+    {
+        let mut buf = vec![1; 2 * block as usize];
+        file.read_at(&mut buf, 0).await.unwrap();
+        assert!(buf.iter().all(|&b| b == 0));
+        assert_eq!(file.metadata().await.unwrap().len(), 4 * block);
+    }
+    if is_supported(file.collapse_range(0, 2 * block).await) {
+        assert_eq!(file.metadata().await.unwrap().len(), 2 * block);
+        let mut buf = vec![0; 2 * block as usize];
+        file.read_at(&mut buf, 0).await.unwrap();
+        assert_eq!(buf, &data[2 * block as usize..]);
+    }
+
+    file.set_len(block).await.unwrap();
+    assert_eq!(file.metadata().await.unwrap().len(), block);
+
+    dir.close().await.unwrap();
+}
+
+fn is_supported(res: std::io::Result<()>) -> bool {
+    match res {
+        Ok(()) => true,
+        Err(err)
+            if err.kind() == ErrorKind::Unsupported
+                || err.raw_os_error() == Some(libc::EOPNOTSUPP) =>
+        {
+            false
+        }
+        Err(err) => panic!("{err}"),
+    }
+}
+
+#[photonio::test]
+async fn advise() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    fs::write(path, vec![1; 64 * 1024]).await.unwrap();
+
+    let file = File::open(path).await.unwrap();
+    for advice in [
+        fs::Advice::Sequential,
+        fs::Advice::Random,
+        fs::Advice::WillNeed,
+        fs::Advice::DontNeed,
+        fs::Advice::NoReuse,
+        fs::Advice::Normal,
+    ] {
+        file.advise(0, 0, advice).await.unwrap();
+    }
+    file.advise(0, 1 << 40, fs::Advice::DontNeed).await.unwrap();
+    file.readahead(0, 64 * 1024).await.unwrap();
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn direct() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    let file = match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .direct(true)
+        .open(path)
+        .await
+    {
+        Ok(file) => file,
+        // The filesystem doesn't support direct I/O.
+        Err(err) if err.kind() == ErrorKind::InvalidInput => return,
+        Err(err) => panic!("{err}"),
+    };
+    let align = file.direct_alignment().await.unwrap();
+    assert!(align.memory().is_power_of_two());
+    assert!(align.offset().is_power_of_two());
+
+    let size = align.offset().max(align.memory()) * 2;
+    let mut buf = AlignedBuf::new(size, align.memory());
+    assert_eq!(buf.as_ptr() as usize % align.memory(), 0);
+    assert_eq!(buf.align(), align.memory());
+    buf.fill(1);
+
+    file.write_all_at(&buf, 0).await.unwrap();
+    let mut read_buf = AlignedBuf::new(size, align.memory());
+    file.read_exact_at(&mut read_buf, 0).await.unwrap();
+    assert_eq!(&read_buf[..], &buf[..]);
+
+    if align.memory() > 1 {
+        let err = file.write_at(&buf[1..size / 2 + 1], 0).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+    if align.offset() > 1 {
+        let err = file.write_at(&buf[..size / 2], 1).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = file.read_at(&mut read_buf[..1], 0).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    // Reads and writes at the cursor are checked the same way.
+    let mut file = file;
+    file.write_all(&buf).await.unwrap();
+    assert_eq!(file.seek(SeekFrom::Current(0)).await.unwrap(), size as u64);
+    file.seek(SeekFrom::Start(0)).await.unwrap();
+    read_buf.fill(0);
+    file.read_exact(&mut read_buf).await.unwrap();
+    assert_eq!(&read_buf[..], &buf[..]);
+    if align.offset() > 1 {
+        file.seek(SeekFrom::Start(1)).await.unwrap();
+        let err = file.write(&buf[..size / 2]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = file.read(&mut read_buf[..size / 2]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn lock() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    fs::write(path, vec![0; 64]).await.unwrap();
+    let a = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .await
+        .unwrap();
+    let b = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .await
+        .unwrap();
+
+    a.lock_exclusive().await.unwrap();
+    let err = b.try_lock_shared().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    a.unlock().unwrap();
+
+    a.lock_shared().await.unwrap();
+    b.try_lock_shared().unwrap();
+    let err = b.try_lock_exclusive().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    a.unlock().unwrap();
+    b.lock_exclusive().await.unwrap();
+    b.unlock().unwrap();
+
+    a.lock_range(0, 16, fs::LockKind::Exclusive).await.unwrap();
+    b.try_lock_range(16, 16, fs::LockKind::Exclusive).unwrap();
+    let err = b.try_lock_range(8, 16, fs::LockKind::Shared).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    a.unlock_range(0, 16).unwrap();
+    b.try_lock_range(0, 0, fs::LockKind::Shared).unwrap();
+    b.unlock().unwrap();
+
+    // Waiting for a lock held elsewhere completes once it is released.
+    a.lock_exclusive().await.unwrap();
+    let b = task::spawn(async move {
+        b.lock_exclusive().await.unwrap();
+        b
+    });
+    a.unlock().unwrap();
+    let b = b.await.unwrap();
+    b.unlock().unwrap();
+
+    // Dropping a waiting lock cancels it, so it isn't acquired later.
+    a.lock_exclusive().await.unwrap();
+    {
+        let lock = b.lock_exclusive();
+        futures::pin_mut!(lock);
+        assert!(futures::poll!(lock.as_mut()).is_pending());
+    }
+    a.unlock().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    a.try_lock_exclusive().unwrap();
+    a.unlock().unwrap();
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn mmap() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    fs::write(path, "hello world").await.unwrap();
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .await
+        .unwrap();
+    // Safety: the file is not modified while it is mapped.
+    let map = unsafe { fs::Mmap::map(&file) }.await.unwrap();
+    assert_eq!(&map[..], b"hello world");
+    map.advise(fs::Advice::WillNeed).unwrap();
+    map.lock().await.unwrap();
+    map.unlock().unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(map.read_at(&mut buf, 6).await.unwrap(), 5);
+    assert_eq!(&buf[..5], b"world");
+    assert_eq!(map.read_at(&mut buf, 20).await.unwrap(), 0);
+
+    drop(map);
+
+    // Safety: the file is only modified through this map.
+    let mut map_mut = unsafe { fs::MmapMut::map_mut(&file) }.await.unwrap();
+    map_mut[..5].copy_from_slice(b"HELLO");
+    map_mut.flush_async().await.unwrap();
+    drop(map_mut);
+    assert_eq!(fs::read(path).await.unwrap(), b"HELLO world");
+
+    fs::write(path, "").await.unwrap();
+    // Safety: the file is not modified while it is mapped.
+    let map = unsafe { fs::Mmap::map(&file) }.await.unwrap();
+    assert!(map.is_empty());
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn watcher() {
+    use fs::{Event, Watcher};
+
+    let dir = fs::TempDir::new().await.unwrap();
+    let root = dir.path();
+
+    let mut watcher = Watcher::new().unwrap();
+    let wd = watcher.add(root).await.unwrap();
+
+    let a = root.join("a");
+    let b = root.join("b");
+    fs::write(&a, "a").await.unwrap();
+    let event = watcher.next_event().await.unwrap();
+    let Event::Create(path) = event else { panic!("{event:?}") };
+    assert_eq!(path.watch(), wd);
+    assert_eq!(path.name().unwrap(), "a");
+    let event = watcher.next_event().await.unwrap();
+    assert!(matches!(event, Event::Modify(path) if path.name().unwrap() == "a"));
+
+    fs::rename(&a, &b).await.unwrap();
+    let event = watcher.next_event().await.unwrap();
+    let Event::Move { from, to } = event else { panic!("{event:?}") };
+    assert_eq!(from.name().unwrap(), "a");
+    assert_eq!(to.name().unwrap(), "b");
+
+    fs::remove_file(&b).await.unwrap();
+    let event = watcher.try_next().await.unwrap().unwrap();
+    assert!(matches!(event, Event::Delete(path) if path.name().unwrap() == "b"));
+
+    watcher.remove(wd).unwrap();
+    assert_eq!(watcher.next_event().await.unwrap(), Event::Removed(wd));
+
+    // Drop the watcher while a poll is pending.
+    watcher.add(root).await.unwrap();
+    {
+        let next = watcher.next_event();
+        futures::pin_mut!(next);
+        assert!(futures::poll!(next).is_pending());
+        // Let the poll be submitted.
+        fs::metadata(root).await.unwrap();
+    }
+    drop(watcher);
+
+    // A watcher with a pending poll can be dropped outside the runtime too.
+    let mut watcher = Watcher::new().unwrap();
+    watcher.add(root).await.unwrap();
+    {
+        let next = watcher.next_event();
+        futures::pin_mut!(next);
+        assert!(futures::poll!(next).is_pending());
+        // Let the poll be submitted.
+        fs::metadata(root).await.unwrap();
+    }
+    std::thread::spawn(move || drop(watcher)).join().unwrap();
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn xattr() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    fs::write(path, "hello").await.unwrap();
+
+    let file = File::open(path).await.unwrap();
+    match file.set_xattr("user.a", b"1").await {
+        Ok(()) => {}
+        // The filesystem doesn't support extended attributes.
+        Err(err) if err.kind() == ErrorKind::Unsupported => return,
+        Err(err) => panic!("{err}"),
+    }
+    assert_eq!(file.get_xattr("user.a").await.unwrap().unwrap(), b"1");
+    assert_eq!(file.get_xattr("user.b").await.unwrap(), None);
+
+    let value = vec![7; 1000];
+    fs::set_xattr(path, "user.b", &value).await.unwrap();
+    assert_eq!(fs::get_xattr(path, "user.b").await.unwrap().unwrap(), value);
+    let mut names = file.list_xattr().await.unwrap();
+    names.sort();
+    assert_eq!(names, ["user.a", "user.b"]);
+
+    file.remove_xattr("user.a").await.unwrap();
+    fs::remove_xattr(path, "user.b").await.unwrap();
+    assert!(fs::list_xattr(path).await.unwrap().is_empty());
+    assert!(fs::remove_xattr(path, "user.b").await.is_err());
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn space_usage() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let path = &dir.path().join("test.txt");
+    fs::write(path, "hello").await.unwrap();
+
+    let stats = fs::space(path).await.unwrap();
+    assert!(stats.total_bytes() > 0);
+    assert!(stats.free_bytes() <= stats.total_bytes());
+    assert!(stats.available_bytes() <= stats.free_bytes());
+    assert!(stats.free_inodes() <= stats.total_inodes());
+    assert!(stats.block_size() > 0);
+
+    let file = File::open(path).await.unwrap();
+    let file_stats = file.space().await.unwrap();
+    assert_eq!(file_stats.fs_type(), stats.fs_type());
+    assert_eq!(file_stats.total_bytes(), stats.total_bytes());
+    assert!(fs::space(dir.path().join("missing")).await.is_err());
+
+    dir.close().await.unwrap();
+}
+
+#[photonio::test]
+async fn temp() {
+    let dir = fs::TempDir::new().await.unwrap();
+    let root = dir.path().to_owned();
+    assert!(fs::metadata(&root).await.unwrap().is_dir());
+
+    let file = fs::tempfile_in(&root).await.unwrap();
+    file.write_all_at(b"hello", 0).await.unwrap();
+    let mut buf = [0; 5];
+    file.read_exact_at(&mut buf, 0).await.unwrap();
+    assert_eq!(&buf, b"hello");
+    let mut entries = fs::read_dir(&root).await.unwrap();
+    assert!(entries.next_entry().await.unwrap().is_none());
+
+    let mut named = fs::NamedTempFile::new_in(&root).await.unwrap();
+    named.write_all(b"world").await.unwrap();
+    let path = named.path().to_owned();
+    assert_eq!(fs::read(&path).await.unwrap(), b"world");
+    drop(named);
+    assert!(fs::metadata(&path).await.is_err());
+
+    let named = fs::NamedTempFile::new_in(&root).await.unwrap();
+    let temp = named.path().to_owned();
+    named.file().write_all_at(b"kept", 0).await.unwrap();
+    let target = root.join("kept.txt");
+    let file = named.persist(&target).await.unwrap();
+    assert!(fs::metadata(&temp).await.is_err());
+    assert_eq!(fs::read(&target).await.unwrap(), b"kept");
+    drop(file);
+
+    let nested = fs::TempDir::new_in(&root).await.unwrap();
+    let nested_path = nested.path().to_owned();
+    fs::write(nested_path.join("a"), "a").await.unwrap();
+    drop(nested);
+    assert!(fs::metadata(&nested_path).await.is_err());
+
+    dir.close().await.unwrap();
+    assert!(fs::metadata(&root).await.is_err());
 }
@@ -0,0 +1,93 @@
+use std::os::unix::process::ExitStatusExt;
+
+use photonio::{
+    io::{ReadExt, WriteExt},
+    process::{Command, Stdio},
+};
+
+#[photonio::test]
+async fn output() {
+    let output = Command::new("sh")
+        .args(["-c", "echo out; echo err >&2; exit 3"])
+        .output()
+        .await
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(output.stdout, b"out\n");
+    assert_eq!(output.stderr, b"err\n");
+
+    let status = Command::new("true").status().await.unwrap();
+    assert!(status.success());
+    assert!(Command::new("/nonexistent").status().await.is_err());
+}
+
+#[photonio::test]
+async fn pipes() {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    assert!(child.id() > 0);
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    stdin.write_all(b"hello").await.unwrap();
+    let mut buf = [0; 5];
+    stdout.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello");
+
+    drop(stdin);
+    let mut buf = [0; 1];
+    assert!(stdout.read_exact(&mut buf).await.is_err());
+    assert!(child.wait().await.unwrap().success());
+}
+
+#[photonio::test]
+async fn kill() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    let status = child.wait().await.unwrap();
+    // The child is killed with `SIGKILL`.
+    assert_eq!(status.signal(), Some(9));
+    assert_eq!(child.try_wait().unwrap(), Some(status));
+    child.kill().unwrap();
+}
+
+#[photonio::test]
+async fn output_stdio() {
+    // The standard input is empty by default, instead of the parent's. Replace
+    // the parent's with a pipe that has some data to check that.
+    let mut fds = [0; 2];
+    unsafe {
+        assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+        assert_eq!(libc::write(fds[1], b"parent".as_ptr().cast(), 6), 6);
+        libc::close(fds[1]);
+    }
+    let stdin = unsafe { libc::dup(0) };
+    unsafe { libc::dup2(fds[0], 0) };
+    let mut command = Command::new("cat");
+    let output = command.output().await;
+    unsafe {
+        libc::dup2(stdin, 0);
+        libc::close(stdin);
+        libc::close(fds[0]);
+    }
+    let output = output.unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    // The command is left unchanged.
+    let mut child = command.stdin(Stdio::null()).spawn().unwrap();
+    assert!(child.stdout.is_none());
+    assert!(child.wait().await.unwrap().success());
+
+    // Explicitly configured streams are respected.
+    let output = Command::new("sh")
+        .args(["-c", "echo out; echo err >&2"])
+        .stdout(Stdio::null())
+        .output()
+        .await
+        .unwrap();
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.stderr, b"err\n");
+}
@@ -0,0 +1,54 @@
+use photonio::{fs, io::WriteAt, task};
+
+photonio::task_local! {
+    static REQUEST_ID: u64;
+}
+
+#[photonio::test]
+async fn task_local() {
+    let _: task::AccessError = REQUEST_ID.try_with(|_| ()).unwrap_err();
+    REQUEST_ID
+        .scope(1, async {
+            assert_eq!(REQUEST_ID.get(), 1);
+            let file = fs::tempfile().await.unwrap();
+            file.write_at(b"hello", 0).await.unwrap();
+            assert_eq!(REQUEST_ID.get(), 1);
+
+            REQUEST_ID
+                .scope(2, async {
+                    task::yield_now().await;
+                    assert_eq!(REQUEST_ID.get(), 2);
+                })
+                .await;
+            assert_eq!(REQUEST_ID.get(), 1);
+
+            let handle = task::spawn(REQUEST_ID.scope(3, async {
+                task::yield_now().await;
+                REQUEST_ID.get()
+            }));
+            assert_eq!(handle.await.unwrap(), 3);
+            // Spawned tasks don't inherit the value.
+            let handle = task::spawn(async { REQUEST_ID.try_with(|_| ()).is_err() });
+            assert!(handle.await.unwrap());
+            assert_eq!(REQUEST_ID.sync_scope(4, || REQUEST_ID.get()), 4);
+            assert_eq!(REQUEST_ID.get(), 1);
+        })
+        .await;
+    assert!(REQUEST_ID.try_with(|_| ()).is_err());
+}
+
+#[photonio::test]
+async fn drop_while_borrowed() {
+    let name = String::from("scoped");
+    let future = REQUEST_ID.scope(2, async move { name });
+    REQUEST_ID
+        .scope(1, async {
+            // The value can't be swapped while it is borrowed, so the future is
+            // dropped without it.
+            REQUEST_ID.with(|id| {
+                drop(future);
+                assert_eq!(*id, 1);
+            });
+        })
+        .await;
+}
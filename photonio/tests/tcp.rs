@@ -1,6 +1,6 @@
 use log::trace;
 use photonio::{
-    io::{Read, Write},
+    io::{Read, ReadExt, Write},
     net::{SocketAddr, TcpListener, TcpStream},
     task,
 };
@@ -36,3 +36,24 @@ async fn recv(mut stream: TcpStream) {
     let mut byte = [0; 1];
     stream.read(&mut byte).await.unwrap();
 }
+
+#[photonio::test]
+async fn try_clone() {
+    let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap();
+    let client = task::spawn(async move {
+        let mut stream = TcpStream::connect(server_addr).await.unwrap();
+        let mut reader = stream.try_clone().unwrap();
+        stream.write(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+    });
+    let (mut stream, _) = server.accept().await.unwrap();
+    let mut writer = stream.try_clone().unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+    writer.write(b"pong").await.unwrap();
+    client.await.unwrap();
+}
@@ -0,0 +1,109 @@
+#![cfg(all(target_os = "linux", not(feature = "tokio")))]
+
+use std::{io::ErrorKind, time::Duration};
+
+use photonio::{
+    fs::{self, File, OpenOptions},
+    io::{AlignedBuf, ReadAt, ReadAtExt, WriteAt, WriteAtExt},
+    runtime::{self, Builder},
+};
+
+fn run(builder: Builder) {
+    let rt = builder.num_threads(2).build().unwrap();
+    rt.block_on(async {
+        let dir = fs::TempDir::new().await.unwrap();
+        let path = dir.path().join("test.txt");
+        let file = File::create(&path).await.unwrap();
+        file.write_at(b"hello", 0).await.unwrap();
+        let mut buf = [0; 5];
+        let file = File::open(&path).await.unwrap();
+        file.read_at(&mut buf, 0).await.unwrap();
+        assert_eq!(&buf, b"hello");
+        dir.close().await.unwrap();
+    });
+}
+
+#[test]
+fn queue_sizes() {
+    run(Builder::new().sq_entries(64).cq_entries(256));
+}
+
+#[test]
+fn sqpoll() {
+    run(Builder::new()
+        .sqpoll(true)
+        .sqpoll_idle(Duration::from_millis(10)));
+}
+
+#[test]
+fn sqpoll_long_idle() {
+    // The idle time is capped instead of truncated.
+    run(Builder::new().sqpoll(true).sqpoll_idle(Duration::MAX));
+}
+
+#[test]
+fn iopoll() {
+    run(Builder::new().iopoll(true));
+    let rt = Builder::new().num_threads(2).iopoll(true).build().unwrap();
+    rt.block_on(async {
+        let dir = fs::TempDir::new().await.unwrap();
+        let path = dir.path().join("test.txt");
+        let file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .direct(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => file,
+            // The filesystem doesn't support direct I/O.
+            Err(err) if err.kind() == ErrorKind::InvalidInput => return,
+            Err(err) => panic!("{err}"),
+        };
+        let align = file.direct_alignment().await.unwrap();
+        let size = align.offset().max(align.memory()) * 4;
+        let mut buf = AlignedBuf::new(size, align.memory());
+        buf.fill(1);
+        file.write_all_at(&buf, 0).await.unwrap();
+        let mut read_buf = AlignedBuf::new(size, align.memory());
+        file.read_exact_at(&mut read_buf, 0).await.unwrap();
+        assert_eq!(&read_buf[..], &buf[..]);
+        dir.close().await.unwrap();
+    });
+}
+
+#[test]
+fn taskrun() {
+    run(Builder::new().coop_taskrun(true).defer_taskrun(true));
+}
+
+#[test]
+fn attach_wq() {
+    run(Builder::new().attach_wq(true));
+}
+
+#[test]
+fn invalid_entries() {
+    // The kernel rejects an empty ring.
+    assert!(Builder::new().sq_entries(0).build().is_err());
+    // The error is not blamed on the flags.
+    let err = Builder::new()
+        .sq_entries(0)
+        .sqpoll(true)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[photonio::test]
+async fn features() {
+    let features = runtime::features();
+    // IORING_OP_NOP is supported by all kernels.
+    assert!(features.is_supported(0));
+    // IORING_OP_OPENAT is masked to test the fallbacks.
+    if cfg!(photonio_legacy) {
+        assert!(!features.is_supported(18));
+    }
+}